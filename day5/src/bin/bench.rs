@@ -0,0 +1,78 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Instant;
+use day5::crane::{Crane, CrateMover9000, CrateMover9001};
+use day5::generator::{generate, GenerateOptions};
+use day5::rope::RopePlatforms;
+use day5::{execute_instructions, format_top};
+
+const USAGE: &str = "usage: bench [--stacks N] [--crates N] [--moves N] [--seed N] [--compare] [--write FILE]";
+
+/// Generates a large random procedure and times the treap-backed executor
+/// on it, optionally against the plain `Vec` executor.
+fn main() {
+    let mut stacks = 9;
+    let mut crates = 1_000_000;
+    let mut moves = 1_000_000;
+    let mut seed = 1;
+    let mut compare = false;
+    let mut write = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || args.next()
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or_else(|| exit_with(format!("{} needs a number\n{}", arg, USAGE)));
+        match arg.as_str() {
+            "--stacks" => stacks = number().max(1),
+            "--crates" => crates = number(),
+            "--moves" => moves = number(),
+            "--seed" => seed = number() as u64,
+            "--compare" => compare = true,
+            "--write" => write = Some(args.next().unwrap_or_else(|| exit_with(USAGE))),
+            _ => exit_with(format!("unexpected argument {:?}\n{}", arg, USAGE)),
+        }
+    }
+
+    let start = Instant::now();
+    let puzzle = generate(&GenerateOptions { stacks, crates, moves, ..GenerateOptions::default() }, seed);
+    let (platforms, instructions) = (&puzzle.platforms, &puzzle.instructions);
+    println!("stacks {}, crates {}, moves {} (seed {})", stacks, crates, moves, seed);
+    println!("{:<22}{:>8.3}s", "generate", start.elapsed().as_secs_f64());
+    if let Some(path) = write {
+        fs::write(&path, puzzle.input())
+            .unwrap_or_else(|e| exit_with(e));
+        println!("wrote {}", path);
+    }
+
+    let cranes: [(&dyn Crane, &str); 2] = [
+        (&CrateMover9000, &puzzle.expected_9000),
+        (&CrateMover9001, &puzzle.expected_9001),
+    ];
+    for (crane, expected) in cranes {
+        let start = Instant::now();
+        let mut rope = RopePlatforms::new(platforms);
+        rope.execute_instructions(instructions, crane).unwrap_or_else(|e| exit_with(e));
+        let top = format_top(&rope.top_crates());
+        println!("{:<22}{:>8.3}s  {}", format!("rope {}", crane.name()), start.elapsed().as_secs_f64(), top);
+        if top != expected {
+            exit_with("rope executor disagrees with the generated answer");
+        }
+
+        if compare {
+            let start = Instant::now();
+            let mut stacks = platforms.clone();
+            execute_instructions(instructions.clone(), &mut stacks, crane).unwrap_or_else(|e| exit_with(e));
+            let expect = format_top(&day5::top_crates(&stacks));
+            println!("{:<22}{:>8.3}s  {}", format!("vec {}", crane.name()), start.elapsed().as_secs_f64(), expect);
+            if expect != top {
+                exit_with("executors disagree");
+            }
+        }
+    }
+}
+
+fn exit_with(error: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1);
+}
@@ -0,0 +1,235 @@
+use std::fmt;
+
+use crate::crane::{apply_lift, Crane, Lift};
+use crate::error::CargoError;
+use crate::{check_move, execute_move, reverse_move, Move};
+
+/// What happens when a move would stack crates above a stack's capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Stop the procedure with [`CargoError::Overflow`].
+    Error,
+    /// Carry out the move, then lift the crates above the capacity over to
+    /// the next stack, wrapping around after the last one.
+    Spill,
+    /// Skip the move and carry on with the next one.
+    Reject,
+}
+
+impl Overflow {
+    pub fn from_name(name: &str) -> Option<Overflow> {
+        match name {
+            "error" => Some(Overflow::Error),
+            "spill" => Some(Overflow::Spill),
+            "reject" => Some(Overflow::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// The most crates each stack may hold; `None` means no limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capacity {
+    pub limits: Vec<Option<usize>>,
+    pub overflow: Overflow,
+}
+
+impl Capacity {
+    /// The same limit for all `stacks` stacks.
+    pub fn uniform(stacks: usize, limit: usize, overflow: Overflow) -> Self {
+        Capacity { limits: vec![Some(limit); stacks], overflow }
+    }
+
+    /// The limit of a 0-based stack. Stacks past the end of `limits` have none.
+    pub fn limit(&self, stack: usize) -> Option<usize> {
+        self.limits.get(stack).copied().flatten()
+    }
+}
+
+/// How an over-capacity move was dealt with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Rejected,
+    /// `crates` crates went on to stack `to`, numbered from 1.
+    Spilled { to: usize, crates: usize },
+}
+
+/// A move that would have put `height` crates on a stack with room for
+/// `capacity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub line: usize,
+    pub stack: usize,
+    pub height: usize,
+    pub capacity: usize,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: stack {} would hold {} crates, capacity {}: ", self.line, self.stack, self.height, self.capacity)?;
+        match self.outcome {
+            Outcome::Rejected => write!(f, "move rejected"),
+            Outcome::Spilled { to, crates } => write!(f, "{} crates spilled onto stack {}", crates, to),
+        }
+    }
+}
+
+/// Runs the procedure like [`crate::execute_instructions`] while keeping
+/// every stack within `capacity`, and returns the violations that were
+/// rejected or spilled. The starting drawing is not checked. On error the
+/// platforms are left as they were before the failing move.
+pub fn execute_with_capacity(instructions: &[Move], platforms: &mut [Vec<String>], crane: &dyn Crane, capacity: &Capacity) -> Result<Vec<Violation>, CargoError> {
+    let mut violations = Vec::new();
+    for instruction in instructions {
+        check_move(instruction, platforms)?;
+        let (source, target) = (instruction.source - 1, instruction.target - 1);
+        let height = if source == target {
+            platforms[target].len()
+        } else {
+            platforms[target].len() + instruction.amount
+        };
+        let limit = match capacity.limit(target) {
+            Some(limit) if height > limit => limit,
+            _ => {
+                execute_move(instruction, platforms, crane)?;
+                continue;
+            },
+        };
+        let violation = |outcome| Violation { line: instruction.line, stack: instruction.target, height, capacity: limit, outcome };
+        match capacity.overflow {
+            Overflow::Error => return Err(CargoError::Overflow {
+                line: instruction.line,
+                stack: instruction.target,
+                height,
+                capacity: limit,
+            }),
+            Overflow::Reject => violations.push(violation(Outcome::Rejected)),
+            Overflow::Spill => {
+                execute_move(instruction, platforms, crane)?;
+                if let Err(error) = spill(instruction, platforms, capacity, &mut violations) {
+                    reverse_move(instruction, platforms, crane)?;
+                    return Err(error);
+                }
+            },
+        }
+    }
+    Ok(violations)
+}
+
+/// Moves whatever sits above the capacity of the move's target on to the
+/// next stacks until every stack fits, or fails once the spill has gone all
+/// the way around. A failed spill takes its lifts back and leaves no
+/// violations behind.
+fn spill(instruction: &Move, platforms: &mut [Vec<String>], capacity: &Capacity, violations: &mut Vec<Violation>) -> Result<(), CargoError> {
+    let first = violations.len();
+    let mut stack = instruction.target - 1;
+    for _ in 1..platforms.len() {
+        let height = platforms[stack].len();
+        let Some(limit) = capacity.limit(stack).filter(|limit| height > *limit) else {
+            return Ok(());
+        };
+        let next = (stack + 1) % platforms.len();
+        let crates = height - limit;
+        apply_lift(platforms, stack, next, Lift::AllAtOnce(crates));
+        violations.push(Violation {
+            line: instruction.line,
+            stack: stack + 1,
+            height,
+            capacity: limit,
+            outcome: Outcome::Spilled { to: next + 1, crates },
+        });
+        stack = next;
+    }
+    let limit = match capacity.limit(stack) {
+        Some(limit) if platforms[stack].len() > limit => limit,
+        _ => return Ok(()),
+    };
+    let error = CargoError::Overflow {
+        line: instruction.line,
+        stack: stack + 1,
+        height: platforms[stack].len(),
+        capacity: limit,
+    };
+    for violation in violations.drain(first..).rev() {
+        if let Outcome::Spilled { to, crates } = violation.outcome {
+            apply_lift(platforms, to - 1, violation.stack - 1, Lift::AllAtOnce(crates));
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::platforms_from;
+
+    fn procedure() -> Vec<Move> {
+        Vec::from([
+            Move { line: 1, amount: 2, source: 1, target: 2 },
+            Move { line: 2, amount: 1, source: 3, target: 1 },
+        ])
+    }
+
+    #[test]
+    fn it_runs_unlimited_stacks_as_usual() {
+        let mut platforms = platforms_from(&["ABC", "D", "E"]);
+        let capacity = Capacity { limits: Vec::from([None, Some(3)]), overflow: Overflow::Error };
+        let violations = execute_with_capacity(&procedure(), &mut platforms, &CrateMover9000, &capacity).unwrap();
+        assert!(violations.is_empty());
+        assert_eq!(platforms, platforms_from(&["AE", "DCB", ""]));
+    }
+
+    #[test]
+    fn it_stops_on_overflow() {
+        let mut platforms = platforms_from(&["ABC", "D", "E"]);
+        let capacity = Capacity::uniform(3, 2, Overflow::Error);
+        let actual = execute_with_capacity(&procedure(), &mut platforms, &CrateMover9000, &capacity);
+        assert_eq!(actual, Err(CargoError::Overflow { line: 1, stack: 2, height: 3, capacity: 2 }));
+        assert_eq!(platforms, platforms_from(&["ABC", "D", "E"]));
+    }
+
+    #[test]
+    fn it_rejects_moves() {
+        let mut platforms = platforms_from(&["ABC", "D", "E"]);
+        let capacity = Capacity::uniform(3, 2, Overflow::Reject);
+        let violations = execute_with_capacity(&procedure(), &mut platforms, &CrateMover9000, &capacity).unwrap();
+        let expect = Vec::from([
+            Violation { line: 1, stack: 2, height: 3, capacity: 2, outcome: Outcome::Rejected },
+            Violation { line: 2, stack: 1, height: 4, capacity: 2, outcome: Outcome::Rejected },
+        ]);
+        assert_eq!(violations, expect);
+        assert_eq!(platforms, platforms_from(&["ABC", "D", "E"]));
+        assert_eq!(violations[0].to_string(), "line 1: stack 2 would hold 3 crates, capacity 2: move rejected");
+    }
+
+    #[test]
+    fn it_spills_onto_the_next_stacks() {
+        let mut platforms = platforms_from(&["ABCD", "E", "F"]);
+        let capacity = Capacity::uniform(3, 2, Overflow::Spill);
+        let instructions = [Move { line: 5, amount: 3, source: 1, target: 2 }];
+        let violations = execute_with_capacity(&instructions, &mut platforms, &CrateMover9001, &capacity).unwrap();
+        let expect = Vec::from([
+            Violation { line: 5, stack: 2, height: 4, capacity: 2, outcome: Outcome::Spilled { to: 3, crates: 2 } },
+            Violation { line: 5, stack: 3, height: 3, capacity: 2, outcome: Outcome::Spilled { to: 1, crates: 1 } },
+        ]);
+        assert_eq!(violations, expect);
+        assert_eq!(platforms, platforms_from(&["AD", "EB", "FC"]));
+        assert_eq!(violations[1].to_string(), "line 5: stack 3 would hold 3 crates, capacity 2: 1 crates spilled onto stack 1");
+
+        let mut platforms = platforms_from(&["ABC", "DE"]);
+        let capacity = Capacity::uniform(2, 2, Overflow::Spill);
+        let instructions = [Move { line: 1, amount: 1, source: 1, target: 2 }];
+        let actual = execute_with_capacity(&instructions, &mut platforms, &CrateMover9001, &capacity);
+        assert_eq!(actual, Err(CargoError::Overflow { line: 1, stack: 1, height: 3, capacity: 2 }));
+        assert_eq!(platforms, platforms_from(&["ABC", "DE"]));
+
+        let mut platforms = platforms_from(&["ABC", "DE", "FG"]);
+        let capacity = Capacity::uniform(3, 2, Overflow::Spill);
+        let instructions = [Move { line: 4, amount: 2, source: 1, target: 2 }];
+        let actual = execute_with_capacity(&instructions, &mut platforms, &CrateMover9000, &capacity);
+        assert_eq!(actual, Err(CargoError::Overflow { line: 4, stack: 1, height: 3, capacity: 2 }));
+        assert_eq!(platforms, platforms_from(&["ABC", "DE", "FG"]));
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+
+use crate::crane::{apply_lift, Crane, Lift};
+use crate::error::CargoError;
+use crate::{check_move, Move};
+
+/// What one unit of crane work costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostModel {
+    /// Every trip of the arm, however many crates it carries.
+    pub per_lift: u64,
+    /// Every stack the arm passes on a trip, so a trip from stack 1 to
+    /// stack 4 travels 3.
+    pub per_distance: u64,
+    /// Every crate carried.
+    pub per_crate: u64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        CostModel {
+            per_lift: 1,
+            per_distance: 1,
+            per_crate: 1,
+        }
+    }
+}
+
+/// The work a crane did over a procedure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostReport {
+    pub crane: String,
+    pub lifts: u64,
+    pub distance: u64,
+    pub crates: u64,
+    pub total: u64,
+    /// Crates taken off or put onto each stack.
+    pub wear: Vec<u64>,
+    /// The most crates any stack held at one time, and the first stack,
+    /// numbered from 1, that reached it.
+    pub peak_height: usize,
+    pub peak_stack: usize,
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}: total cost {}", self.crane, self.total)?;
+        writeln!(f, "  {} lifts, travel {}, {} crates moved", self.lifts, self.distance, self.crates)?;
+        let wear: Vec<_> = self.wear.iter().map(u64::to_string).collect();
+        writeln!(f, "  wear per stack: {}", wear.join(" "))?;
+        write!(f, "  peak height {} on stack {}", self.peak_height, self.peak_stack)
+    }
+}
+
+/// Runs the procedure on a copy of `platforms` and adds up what `crane`
+/// spends on it. A one-at-a-time lift of `n` crates is `n` trips of the
+/// arm; an all-at-once lift is a single trip.
+pub fn measure(platforms: &[Vec<String>], instructions: &[Move], crane: &dyn Crane, model: &CostModel) -> Result<CostReport, CargoError> {
+    let mut platforms = platforms.to_vec();
+    let (peak_stack, peak_height) = platforms.iter()
+        .enumerate()
+        .fold((0, 0), |peak, (i, platform)| if platform.len() > peak.1 { (i, platform.len()) } else { peak });
+    let mut report = CostReport {
+        crane: crane.name().to_string(),
+        lifts: 0,
+        distance: 0,
+        crates: 0,
+        total: 0,
+        wear: vec![0; platforms.len()],
+        peak_height,
+        peak_stack: peak_stack + 1,
+    };
+    for instruction in instructions {
+        check_move(instruction, &platforms)?;
+        let (source, target) = (instruction.source - 1, instruction.target - 1);
+        if source == target {
+            // The crane carries nothing, as in `apply_lift`.
+            continue;
+        }
+        for lift in crane.lifts(instruction.amount) {
+            let count = lift.count() as u64;
+            let trips = match lift {
+                Lift::OneAtATime(_) => count,
+                Lift::AllAtOnce(_) => count.min(1),
+            };
+            report.lifts += trips;
+            report.distance += trips * source.abs_diff(target) as u64;
+            report.crates += count;
+            report.wear[source] += count;
+            report.wear[target] += count;
+            apply_lift(&mut platforms, source, target, lift);
+        }
+        if platforms[target].len() > report.peak_height {
+            report.peak_height = platforms[target].len();
+            report.peak_stack = instruction.target;
+        }
+    }
+    report.total = report.lifts * model.per_lift
+        + report.distance * model.per_distance
+        + report.crates * model.per_crate;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{BatchCrane, CrateMover9000, CrateMover9001};
+    use crate::parse_input;
+    use crate::tests::TEST_INPUT;
+
+    #[test]
+    fn it_measures_both_cranes() {
+        let (platforms, instructions) = parse_input(TEST_INPUT).unwrap();
+        let model = CostModel { per_lift: 10, per_distance: 2, per_crate: 1 };
+
+        let report = measure(&platforms, &instructions, &CrateMover9000, &model).unwrap();
+        assert_eq!((report.lifts, report.distance, report.crates), (7, 10, 7));
+        assert_eq!(report.total, 70 + 20 + 7);
+        assert_eq!(report.wear, Vec::from([7, 4, 3]));
+        assert_eq!((report.peak_height, report.peak_stack), (4, 3));
+
+        let report = measure(&platforms, &instructions, &CrateMover9001, &model).unwrap();
+        assert_eq!((report.lifts, report.distance, report.crates), (4, 5, 7));
+        assert_eq!(report.total, 40 + 10 + 7);
+        assert_eq!(report.wear, Vec::from([7, 4, 3]));
+
+        let report = measure(&platforms, &instructions, &BatchCrane { capacity: 2 }, &model).unwrap();
+        assert_eq!(report.lifts, 5);
+        assert_eq!(report.crane, "batch crane");
+    }
+
+    #[test]
+    fn it_reports() {
+        let (platforms, instructions) = parse_input(TEST_INPUT).unwrap();
+        let report = measure(&platforms, &instructions, &CrateMover9001, &CostModel::default()).unwrap();
+        let expect = concat!(
+            "CrateMover 9001: total cost 16\n",
+            "  4 lifts, travel 5, 7 crates moved\n",
+            "  wear per stack: 7 4 3\n",
+            "  peak height 4 on stack 3",
+        );
+        assert_eq!(report.to_string(), expect);
+    }
+
+    #[test]
+    fn it_charges_nothing_for_moves_onto_the_same_stack() {
+        let (platforms, _) = parse_input(TEST_INPUT).unwrap();
+        let instructions = [Move { line: 6, amount: 2, source: 1, target: 1 }];
+        let report = measure(&platforms, &instructions, &CrateMover9000, &CostModel::default()).unwrap();
+        assert_eq!((report.lifts, report.distance, report.crates, report.total), (0, 0, 0, 0));
+        assert_eq!(report.wear, Vec::from([0, 0, 0]));
+
+        let instructions = [Move { line: 6, amount: 3, source: 1, target: 1 }];
+        let actual = measure(&platforms, &instructions, &CrateMover9000, &CostModel::default());
+        assert_eq!(actual, Err(CargoError::EmptyStack { line: 6, stack: 1, wanted: 3, available: 2 }));
+    }
+
+    #[test]
+    fn it_stops_on_bad_moves() {
+        let (platforms, _) = parse_input(TEST_INPUT).unwrap();
+        let instructions = [Move { line: 6, amount: 4, source: 1, target: 3 }];
+        let actual = measure(&platforms, &instructions, &CrateMover9000, &CostModel::default());
+        assert_eq!(actual, Err(CargoError::EmptyStack { line: 6, stack: 1, wanted: 4, available: 2 }));
+    }
+}
@@ -0,0 +1,126 @@
+/// A single transfer of crates performed by a crane arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lift {
+    /// Crates are moved one by one, so they land in reverse order.
+    OneAtATime(usize),
+    /// Crates are moved together, so they keep their order.
+    AllAtOnce(usize),
+}
+
+impl Lift {
+    pub fn count(&self) -> usize {
+        match self {
+            Lift::OneAtATime(count) | Lift::AllAtOnce(count) => *count,
+        }
+    }
+}
+
+/// A crane model decides how a `move N from A to B` instruction is carried out.
+pub trait Crane {
+    fn name(&self) -> &str;
+
+    /// Splits moving `amount` crates into the lifts this crane performs,
+    /// in the order they happen.
+    fn lifts(&self, amount: usize) -> Vec<Lift>;
+}
+
+/// Moves crates one at a time.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> &str {
+        "CrateMover 9000"
+    }
+
+    fn lifts(&self, amount: usize) -> Vec<Lift> {
+        Vec::from([Lift::OneAtATime(amount)])
+    }
+}
+
+/// Moves any number of crates at once.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> &str {
+        "CrateMover 9001"
+    }
+
+    fn lifts(&self, amount: usize) -> Vec<Lift> {
+        Vec::from([Lift::AllAtOnce(amount)])
+    }
+}
+
+/// Moves crates together, but never more than `capacity` per lift.
+pub struct BatchCrane {
+    pub capacity: usize,
+}
+
+impl Crane for BatchCrane {
+    fn name(&self) -> &str {
+        "batch crane"
+    }
+
+    fn lifts(&self, amount: usize) -> Vec<Lift> {
+        let capacity = self.capacity.max(1);
+        let mut lifts = Vec::new();
+        let mut remaining = amount;
+        while remaining > 0 {
+            let count = remaining.min(capacity);
+            lifts.push(Lift::AllAtOnce(count));
+            remaining -= count;
+        }
+        lifts
+    }
+}
+
+/// Performs a single lift between two stacks, given as 0-based indices.
+/// Putting crates back onto the stack they came from changes nothing.
+pub fn apply_lift<T>(platforms: &mut [Vec<T>], source: usize, target: usize, lift: Lift) {
+    if source == target {
+        return;
+    }
+    let from = &mut platforms[source];
+    let mut cargo = from.split_off(from.len() - lift.count());
+    if let Lift::OneAtATime(_) = lift {
+        cargo.reverse();
+    }
+    platforms[target].append(&mut cargo);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_batch_crane_lifts() {
+        let crane = BatchCrane { capacity: 2 };
+        let actual = crane.lifts(5);
+        let expect = Vec::from([
+            Lift::AllAtOnce(2),
+            Lift::AllAtOnce(2),
+            Lift::AllAtOnce(1),
+        ]);
+        assert_eq!(actual, expect);
+        assert!(crane.lifts(0).is_empty());
+    }
+
+    #[test]
+    fn it_apply_lift() {
+        let mut platforms = Vec::from([
+            Vec::from(['Z', 'N', 'D']),
+            Vec::from(['P']),
+        ]);
+        apply_lift(&mut platforms, 0, 1, Lift::OneAtATime(2));
+        assert_eq!(platforms[1], Vec::from(['P', 'D', 'N']));
+
+        apply_lift(&mut platforms, 1, 0, Lift::AllAtOnce(2));
+        let expect = Vec::from([
+            Vec::from(['Z', 'D', 'N']),
+            Vec::from(['P']),
+        ]);
+        assert_eq!(platforms, expect);
+
+        apply_lift(&mut platforms, 0, 0, Lift::OneAtATime(3));
+        assert_eq!(platforms, expect);
+    }
+}
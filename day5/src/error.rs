@@ -0,0 +1,101 @@
+use std::error::Error;
+use std::fmt;
+
+/// Everything that can go wrong while reading or running a crane procedure.
+/// Line numbers are 1-based and refer to the whole input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CargoError {
+    /// There is no blank line between the drawing and the procedure.
+    MissingSeparator,
+    /// The blank line is the first line, so there is no stack number footer.
+    MissingFooter,
+    /// The footer does not number the stacks 1, 2, 3... from the left.
+    BadFooter { line: usize, column: usize, found: String, expected: usize },
+    /// A drawing cell is neither empty nor a bracketed crate like `[A]`.
+    MalformedCrate { line: usize, column: usize, cell: String },
+    /// A crate label is empty or holds whitespace or `]`, so it cannot be
+    /// drawn in a way that reads back the same.
+    BadLabel { label: String },
+    /// A number in a `move` instruction does not fit into a `usize`.
+    BadNumber { line: usize, value: String },
+    /// A `move` instruction refers to a stack that does not exist.
+    BadStackIndex { line: usize, stack: usize, stacks: usize },
+    /// A `move` instruction wants more crates than the source stack holds.
+    EmptyStack { line: usize, stack: usize, wanted: usize, available: usize },
+    /// A crane script line does not follow the instruction language.
+    Syntax { line: usize, column: usize, message: String },
+    /// A move would stack more crates than a stack's capacity allows.
+    Overflow { line: usize, stack: usize, height: usize, capacity: usize },
+    /// The target arrangement does not hold the same stacks and crates as
+    /// the start, so no procedure can reach it.
+    MismatchedCrates,
+    /// The planner gave up after looking at `explored` arrangements.
+    PlanNotFound { explored: usize },
+    /// Reading a streamed input failed after `line` lines.
+    Read { line: usize, message: String },
+}
+
+impl CargoError {
+    /// The line and, where there is one, the column the error points at.
+    pub fn location(&self) -> Option<(usize, Option<usize>)> {
+        match self {
+            CargoError::MissingFooter => Some((1, None)),
+            CargoError::BadFooter { line, column, .. }
+            | CargoError::MalformedCrate { line, column, .. }
+            | CargoError::Syntax { line, column, .. } => Some((*line, Some(*column))),
+            CargoError::BadNumber { line, .. }
+            | CargoError::BadStackIndex { line, .. }
+            | CargoError::EmptyStack { line, .. }
+            | CargoError::Overflow { line, .. }
+            | CargoError::Read { line, .. } => Some((*line, None)),
+            CargoError::MissingSeparator
+            | CargoError::BadLabel { .. }
+            | CargoError::MismatchedCrates
+            | CargoError::PlanNotFound { .. } => None,
+        }
+    }
+
+    /// What went wrong, without the [`CargoError::location`].
+    pub fn message(&self) -> String {
+        match self {
+            CargoError::MissingSeparator =>
+                String::from("no blank line between the drawing and the procedure"),
+            CargoError::MissingFooter =>
+                String::from("drawing has no stack number footer"),
+            CargoError::BadFooter { found, expected, .. } =>
+                format!("expected stack number {} in the footer, found {:?}", expected, found),
+            CargoError::MalformedCrate { cell, .. } =>
+                format!("malformed crate cell {:?}", cell),
+            CargoError::BadLabel { label } =>
+                format!("crate label {:?} cannot be drawn", label),
+            CargoError::BadNumber { value, .. } =>
+                format!("{:?} is not a valid number", value),
+            CargoError::BadStackIndex { stack, stacks, .. } =>
+                format!("stack {} does not exist, there are {} stacks", stack, stacks),
+            CargoError::EmptyStack { stack, wanted, available, .. } =>
+                format!("cannot move {} crates from stack {}, it only holds {}", wanted, stack, available),
+            CargoError::Syntax { message, .. } =>
+                message.clone(),
+            CargoError::Overflow { stack, height, capacity, .. } =>
+                format!("stack {} would hold {} crates, its capacity is {}", stack, height, capacity),
+            CargoError::MismatchedCrates =>
+                String::from("target arrangement does not have the same stacks and crates"),
+            CargoError::PlanNotFound { explored } =>
+                format!("no procedure found after exploring {} arrangements", explored),
+            CargoError::Read { message, .. } =>
+                format!("could not read input: {}", message),
+        }
+    }
+}
+
+impl fmt::Display for CargoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location() {
+            Some((line, Some(column))) => write!(f, "line {}, column {}: {}", line, column, self.message()),
+            Some((line, None)) => write!(f, "line {}: {}", line, self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl Error for CargoError {}
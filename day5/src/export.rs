@@ -0,0 +1,149 @@
+use std::fmt::Write;
+
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::simulation::{Simulation, Snapshot};
+use crate::Move;
+
+/// Machine-readable formats for platform snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn write(&self, snapshots: &[Snapshot]) -> String {
+        match self {
+            Format::Json => to_json(snapshots),
+            Format::Csv => to_csv(snapshots),
+        }
+    }
+}
+
+/// The starting arrangement as step 0, followed by the arrangement after
+/// every instruction.
+pub fn snapshots(platforms: Vec<Vec<String>>, instructions: Vec<Move>, crane: &dyn Crane) -> Result<Vec<Snapshot>, CargoError> {
+    let mut simulation = Simulation::new(platforms, instructions, crane);
+    let mut snapshots = Vec::from([simulation.snapshot()]);
+    for step in simulation.by_ref() {
+        snapshots.push(step?.snapshot);
+    }
+    Ok(snapshots)
+}
+
+/// A JSON array with one object per snapshot:
+/// `{"step":0,"stacks":[{"stack":1,"crates":["Z","N"]},...]}`, crates bottom
+/// to top.
+pub fn to_json(snapshots: &[Snapshot]) -> String {
+    let mut json = String::from("[");
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        write!(json, "{}\n  {{\"step\":{},\"stacks\":[", separator, snapshot.step).unwrap();
+        for (stack, platform) in snapshot.platforms.iter().enumerate() {
+            let separator = if stack == 0 { "" } else { "," };
+            let crates: Vec<_> = platform.iter().map(|label| json_string(label)).collect();
+            write!(json, "{}{{\"stack\":{},\"crates\":[{}]}}", separator, stack + 1, crates.join(",")).unwrap();
+        }
+        json.push_str("]}");
+    }
+    json.push_str(if snapshots.is_empty() { "]\n" } else { "\n]\n" });
+    json
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// One row per stack and step, `step,stack,crates`, with the crates of a
+/// stack separated by spaces from bottom to top. Empty stacks get an empty
+/// field, so every snapshot has a row for every stack.
+pub fn to_csv(snapshots: &[Snapshot]) -> String {
+    let mut csv = String::from("step,stack,crates\n");
+    for snapshot in snapshots {
+        for (stack, platform) in snapshot.platforms.iter().enumerate() {
+            writeln!(csv, "{},{},{}", snapshot.step, stack + 1, csv_field(&platform.join(" "))).unwrap();
+        }
+    }
+    csv
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::CrateMover9001;
+    use crate::tests::platforms_from;
+
+    fn example() -> Vec<Snapshot> {
+        let instructions = Vec::from([
+            Move { line: 4, amount: 1, source: 2, target: 1 },
+            Move { line: 5, amount: 2, source: 1, target: 3 },
+        ]);
+        snapshots(platforms_from(&["ZN", "MCD", ""]), instructions, &CrateMover9001).unwrap()
+    }
+
+    #[test]
+    fn it_writes_json() {
+        let expect = concat!(
+            "[\n",
+            "  {\"step\":0,\"stacks\":[{\"stack\":1,\"crates\":[\"Z\",\"N\"]},{\"stack\":2,\"crates\":[\"M\",\"C\",\"D\"]},{\"stack\":3,\"crates\":[]}]},\n",
+            "  {\"step\":1,\"stacks\":[{\"stack\":1,\"crates\":[\"Z\",\"N\",\"D\"]},{\"stack\":2,\"crates\":[\"M\",\"C\"]},{\"stack\":3,\"crates\":[]}]},\n",
+            "  {\"step\":2,\"stacks\":[{\"stack\":1,\"crates\":[\"Z\"]},{\"stack\":2,\"crates\":[\"M\",\"C\"]},{\"stack\":3,\"crates\":[\"N\",\"D\"]}]}\n",
+            "]\n",
+        );
+        assert_eq!(Format::Json.write(&example()), expect);
+        assert_eq!(to_json(&[]), "[]\n");
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+
+    #[test]
+    fn it_writes_csv() {
+        let expect = concat!(
+            "step,stack,crates\n",
+            "0,1,Z N\n",
+            "0,2,M C D\n",
+            "0,3,\n",
+            "1,1,Z N D\n",
+            "1,2,M C\n",
+            "1,3,\n",
+            "2,1,Z\n",
+            "2,2,M C\n",
+            "2,3,N D\n",
+        );
+        assert_eq!(Format::Csv.write(&example()), expect);
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn it_stops_at_the_first_bad_step() {
+        let instructions = Vec::from([Move { line: 7, amount: 2, source: 1, target: 2 }]);
+        let actual = snapshots(platforms_from(&["A", ""]), instructions, &CrateMover9001);
+        assert_eq!(actual, Err(CargoError::EmptyStack { line: 7, stack: 1, wanted: 2, available: 1 }));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::crane::{CrateMover9000, CrateMover9001};
+use crate::render::render_input;
+use crate::rng::Rng;
+use crate::rope::RopePlatforms;
+use crate::{format_top, Move};
+
+/// Shape of a generated puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerateOptions {
+    pub stacks: usize,
+    /// Total number of crates, spread randomly over the stacks.
+    pub crates: usize,
+    pub moves: usize,
+    /// Largest number of crates one move takes; `None` allows the whole stack.
+    pub max_amount: Option<usize>,
+    /// Number of letters per crate label.
+    pub label_width: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            stacks: 9,
+            crates: 50,
+            moves: 100,
+            max_amount: None,
+            label_width: 1,
+        }
+    }
+}
+
+/// A random but valid puzzle and the answers for both crane models.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub platforms: Vec<Vec<String>>,
+    pub instructions: Vec<Move>,
+    pub expected_9000: String,
+    pub expected_9001: String,
+}
+
+impl Puzzle {
+    /// The puzzle written out the way the input file looks.
+    pub fn input(&self) -> String {
+        render_input(&self.platforms, &self.instructions).expect("generated labels can be drawn")
+    }
+}
+
+/// Generates a puzzle from `seed`; the same options and seed always give the
+/// same puzzle. Stack heights are tracked while moves are drawn, so no move
+/// ever takes more crates than its source stack holds. The answers come from
+/// the treap executor, which shares no stack code with the `Vec` executor
+/// behind `parse_cargo`.
+pub fn generate(options: &GenerateOptions, seed: u64) -> Puzzle {
+    let stacks = options.stacks.max(1);
+    let mut rng = Rng::new(seed);
+    let mut platforms = vec![Vec::new(); stacks];
+    for _ in 0..options.crates {
+        let label: String = (0..options.label_width.max(1))
+            .map(|_| char::from(b'A' + rng.below(26) as u8))
+            .collect();
+        platforms[rng.below(stacks)].push(label);
+    }
+
+    let mut heights: Vec<_> = platforms.iter().map(Vec::len).collect();
+    let mut instructions = Vec::with_capacity(options.moves);
+    // The drawing has a row per crate of the tallest stack, then the footer
+    // and the blank line.
+    let first_line = heights.iter().max().unwrap_or(&0) + 3;
+    for i in 0..options.moves {
+        let source = rng.below(stacks);
+        let target = rng.below(stacks);
+        let most = options.max_amount.map_or(heights[source], |max| max.min(heights[source]));
+        let amount = rng.below(most + 1);
+        heights[source] -= amount;
+        heights[target] += amount;
+        instructions.push(Move { line: first_line + i, amount, source: source + 1, target: target + 1 });
+    }
+
+    let expected = |crane| {
+        let mut rope = RopePlatforms::new(&platforms);
+        rope.execute_instructions(&instructions, crane).expect("generated moves are valid");
+        format_top(&rope.top_crates())
+    };
+    let expected_9000 = expected(&CrateMover9000);
+    let expected_9001 = expected(&CrateMover9001);
+    Puzzle { platforms, instructions, expected_9000, expected_9001 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn it_is_reproducible() {
+        let options = GenerateOptions::default();
+        assert_eq!(generate(&options, 3), generate(&options, 3));
+        assert_ne!(generate(&options, 3).input(), generate(&options, 4).input());
+    }
+
+    #[test]
+    fn it_generates_a_parsable_input() {
+        let options = GenerateOptions { stacks: 12, crates: 40, moves: 30, max_amount: Some(3), label_width: 2 };
+        let puzzle = generate(&options, 9);
+        assert_eq!(puzzle.platforms.len(), 12);
+        assert_eq!(puzzle.platforms.iter().map(Vec::len).sum::<usize>(), 40);
+        assert!(puzzle.instructions.iter().all(|instruction| instruction.amount <= 3));
+        assert!(puzzle.platforms.iter().flatten().all(|label| label.len() == 2));
+
+        let (platforms, instructions) = parse_input(&puzzle.input()).unwrap();
+        assert_eq!(platforms, puzzle.platforms);
+        assert_eq!(instructions, puzzle.instructions);
+    }
+}
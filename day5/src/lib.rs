@@ -0,0 +1,595 @@
+pub mod capacity;
+pub mod cost;
+pub mod crane;
+pub mod error;
+pub mod export;
+pub mod generator;
+pub mod lint;
+pub mod planner;
+#[cfg(test)]
+mod properties;
+pub mod provenance;
+pub mod render;
+pub mod repl;
+pub mod rng;
+pub mod rope;
+pub mod script;
+pub mod simulation;
+pub mod stream;
+pub mod visual;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+use std::slice::Iter;
+use crane::{apply_lift, Crane};
+use error::CargoError;
+use rope::{RopePlatforms, Storage};
+
+/// A parsed `move N from A to B` instruction. Stacks are numbered from 1
+/// and `line` is where the instruction appears in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub line: usize,
+    pub amount: usize,
+    pub source: usize,
+    pub target: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.amount, self.source, self.target)
+    }
+}
+
+pub fn parse_cargo(input: &str, crane: &dyn Crane) -> Result<Vec<Option<String>>, CargoError> {
+    let (mut platforms, instructions) = parse_input(input)?;
+    execute_instructions(instructions, &mut platforms, crane)?;
+    Ok(top_crates(&platforms))
+}
+
+/// Same as [`parse_cargo`], keeping the stacks in `storage` while the
+/// procedure runs.
+pub fn parse_cargo_in(input: &str, crane: &dyn Crane, storage: Storage) -> Result<Vec<Option<String>>, CargoError> {
+    match storage {
+        Storage::Vec => parse_cargo(input, crane),
+        Storage::Rope => {
+            let (platforms, instructions) = parse_input(input)?;
+            let mut rope = RopePlatforms::new(&platforms);
+            rope.execute_instructions(&instructions, crane)?;
+            Ok(rope.top_crates())
+        },
+    }
+}
+
+/// Splits the input into lines, accepting `\r\n` line endings as well. A
+/// trailing newline leaves an empty last line, as the separator search
+/// expects.
+pub fn split_lines(input: &str) -> Vec<&str> {
+    input.split('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .collect()
+}
+
+/// Splits the input into the starting platforms and the procedure.
+pub fn parse_input(input: &str) -> Result<(Vec<Vec<String>>, Vec<Move>), CargoError> {
+    let lines = split_lines(input);
+    let instruction_idx = find_instruction_line(&lines)?;
+
+    let platform_line = lines[instruction_idx-1];
+    let platform_idxs = build_platform_idxs(platform_line, instruction_idx)?;
+
+    let levels = lines[0..(instruction_idx-1)].iter().rev().collect();
+    let platforms = load_platforms(levels, platform_idxs)?;
+
+    let instructions = parse_move_instructions(
+        lines[(instruction_idx+1)..lines.len()].iter(),
+        instruction_idx + 2,
+    )?;
+    Ok((platforms, instructions))
+}
+
+/// Parses a drawing on its own. Anything after the first blank line, such as
+/// a procedure, is ignored.
+pub fn parse_drawing(input: &str) -> Result<Vec<Vec<String>>, CargoError> {
+    let lines = split_lines(input);
+    let end = lines.iter().position(|l| l.is_empty()).unwrap_or(lines.len());
+    if end == 0 {
+        return Err(CargoError::MissingFooter);
+    }
+    let platform_idxs = build_platform_idxs(lines[end-1], end)?;
+    let levels = lines[0..(end-1)].iter().rev().collect();
+    load_platforms(levels, platform_idxs)
+}
+
+/// The label of the crate on top of each stack, in stack order, with `None`
+/// for an empty stack.
+pub fn top_crates(platforms: &[Vec<String>]) -> Vec<Option<String>> {
+    platforms.iter()
+        .map(|platform| platform.last().cloned())
+        .collect()
+}
+
+/// Joins top labels into the puzzle answer, leaving out empty stacks.
+/// Single-character labels are written together like `CMZ`, longer ones
+/// are separated by spaces.
+pub fn format_top(labels: &[Option<String>]) -> String {
+    let labels: Vec<_> = labels.iter().flatten().map(String::as_str).collect();
+    if labels.iter().all(|label| label.chars().count() == 1) {
+        labels.concat()
+    } else {
+        labels.join(" ")
+    }
+}
+
+pub fn execute_instructions(instructions: Vec<Move>, platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    for instruction in instructions {
+        execute_move(&instruction, platforms, crane)?;
+    }
+    Ok(())
+}
+
+pub fn execute_move(instruction: &Move, platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    check_move(instruction, platforms)?;
+    for lift in crane.lifts(instruction.amount) {
+        apply_lift(platforms, instruction.source-1, instruction.target-1, lift);
+    }
+    Ok(())
+}
+
+/// Undoes the procedure, last instruction first, turning the platforms as
+/// they are after `instructions` back into the starting arrangement.
+pub fn reverse_instructions(instructions: Vec<Move>, platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    for instruction in instructions.iter().rev() {
+        reverse_move(instruction, platforms, crane)?;
+    }
+    Ok(())
+}
+
+/// Undoes a single move by taking each of the crane's lifts back from the
+/// target, in reverse order. One-at-a-time lifts undo themselves and
+/// all-at-once lifts keep their order, so every crane can be reversed.
+pub fn reverse_move(instruction: &Move, platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    let inverse = Move {
+        source: instruction.target,
+        target: instruction.source,
+        ..*instruction
+    };
+    check_move(&inverse, platforms)?;
+    for lift in crane.lifts(instruction.amount).into_iter().rev() {
+        apply_lift(platforms, inverse.source-1, inverse.target-1, lift);
+    }
+    Ok(())
+}
+
+/// Makes sure `instruction` can be carried out on `platforms` as they are now.
+pub fn check_move(instruction: &Move, platforms: &[Vec<String>]) -> Result<(), CargoError> {
+    check_move_with(instruction, platforms.len(), |i| platforms[i].len())
+}
+
+/// Same as [`check_move`] for any stack storage, given the number of stacks
+/// and the height of a stack by its 0-based index.
+pub fn check_move_with(instruction: &Move, stacks: usize, height: impl Fn(usize) -> usize) -> Result<(), CargoError> {
+    for stack in [instruction.source, instruction.target] {
+        if stack == 0 || stack > stacks {
+            return Err(CargoError::BadStackIndex {
+                line: instruction.line,
+                stack,
+                stacks,
+            });
+        }
+    }
+    let available = height(instruction.source-1);
+    if instruction.amount > available {
+        return Err(CargoError::EmptyStack {
+            line: instruction.line,
+            stack: instruction.source,
+            wanted: instruction.amount,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// Parses the procedure, where `first_line` is the line number of the first
+/// entry in `lines`. Lines that are not `move` instructions are skipped.
+pub fn parse_move_instructions(lines: Iter<&str>, first_line: usize) -> Result<Vec<Move>, CargoError> {
+    lines.enumerate()
+        .flat_map(|(i, l)| parse_move_line(l, first_line + i))
+        .collect()
+}
+
+/// Parses one procedure line, or returns `None` if it is not a `move`
+/// instruction at all.
+pub fn parse_move_line(l: &str, line: usize) -> Option<Result<Move, CargoError>> {
+    lazy_static! {
+        static ref MOVE_RE: Regex = Regex::new(r"^\s*move (\d+) from (\d+) to (\d+)\s*$").unwrap();
+    }
+    MOVE_RE.captures(l).map(
+        |cap| {
+            let number = |idx: usize| cap[idx].parse().map_err(
+                |_| CargoError::BadNumber { line, value: cap[idx].to_string() }
+            );
+            Ok(Move {
+                line,
+                amount: number(1)?,
+                source: number(2)?,
+                target: number(3)?,
+            })
+        }
+    )
+}
+
+/// Loads the drawing rows given from bottom to top. Every crate is placed on
+/// the stack whose footer number its brackets overlap, so labels and columns
+/// can be any width. The drawing starts on the first line of the input, which
+/// is what error line numbers are based on.
+pub fn load_platforms(levels: Vec<&&str>, platform_idxs: Vec<(usize, usize)>) -> Result<Vec<Vec<String>>, CargoError> {
+    let mut platforms: Vec<Vec<String>> = vec![Vec::new(); platform_idxs.len()];
+    for (level, l) in levels.iter().enumerate() {
+        let line = levels.len() - level;
+        for (start, end, label) in parse_crate_cells(l, line)? {
+            let mut stacks = platform_idxs.iter()
+                .enumerate()
+                .filter(|(_, (s, e))| *s < end && start < *e)
+                .map(|(i, _)| i);
+            match (stacks.next(), stacks.next()) {
+                (Some(i), None) => platforms[i].push(label),
+                _ => return Err(CargoError::MalformedCrate {
+                    line,
+                    column: start + 1,
+                    cell: format!("[{}]", label),
+                }),
+            }
+        }
+    }
+    Ok(platforms)
+}
+
+/// Splits a drawing row into its `[label]` cells, as character spans with
+/// the end exclusive.
+fn parse_crate_cells(row: &str, line: usize) -> Result<Vec<(usize, usize, String)>, CargoError> {
+    let chars: Vec<_> = row.chars().collect();
+    let mut cells = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let run_end = (i..chars.len())
+            .find(|j| chars[*j].is_whitespace())
+            .unwrap_or(chars.len());
+        match (i+1..run_end).find(|j| chars[*j] == ']') {
+            Some(close) if chars[i] == '[' && close > i+1 => {
+                cells.push((i, close+1, chars[i+1..close].iter().collect()));
+                i = close+1;
+            },
+            _ => return Err(CargoError::MalformedCrate {
+                line,
+                column: i + 1,
+                cell: chars[i..run_end].iter().collect(),
+            }),
+        }
+    }
+    Ok(cells)
+}
+
+pub fn find_instruction_line(lines: &[&str]) -> Result<usize, CargoError> {
+    lines.iter()
+        .enumerate()
+        .find(|(_, l)| l.is_empty())
+        .map(|(i, _)| i)
+        .ok_or(CargoError::MissingSeparator)
+        .and_then(
+            |i| if i == 0 { Err(CargoError::MissingFooter) } else { Ok(i) }
+        )
+}
+
+/// Finds every stack number in the footer on `line` as a `(start, end)`
+/// character span with the end exclusive. The numbers must count up from 1.
+pub fn build_platform_idxs(platform_line: &str, line: usize) -> Result<Vec<(usize, usize)>, CargoError> {
+    let chars: Vec<_> = platform_line.chars().collect();
+    let mut idxs = Vec::new();
+    let mut start = None;
+    for (i, c) in chars.iter().chain([&' ']).enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                let found: String = chars[s..i].iter().collect();
+                let expected = idxs.len() + 1;
+                if found.parse::<usize>().ok() != Some(expected) {
+                    return Err(CargoError::BadFooter { line, column: s + 1, found, expected });
+                }
+                idxs.push((s, i));
+                start = None;
+            },
+            _ => (),
+        }
+    }
+    Ok(idxs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crane::{BatchCrane, CrateMover9000, CrateMover9001};
+
+    /// Turns every char into a single-character crate label, bottom to top.
+    pub(crate) fn labels(stack: &str) -> Vec<String> {
+        stack.chars().map(String::from).collect()
+    }
+
+    pub(crate) fn platforms_from(stacks: &[&str]) -> Vec<Vec<String>> {
+        stacks.iter().map(|stack| labels(stack)).collect()
+    }
+
+    /// Top crates as [`top_crates`] returns them, with `""` for an empty stack.
+    pub(crate) fn tops(labels: &[&str]) -> Vec<Option<String>> {
+        labels.iter().map(|label| Some(label.to_string()).filter(|label| !label.is_empty())).collect()
+    }
+
+    pub(crate) const TEST_INPUT: &str = concat!(
+        "    [D]    \n",
+        "[N] [C]    \n",
+        "[Z] [M] [P]\n",
+        " 1   2   3 \n",
+        "\n",
+        "move 1 from 2 to 1\n",
+        "move 3 from 1 to 3\n",
+        "move 2 from 2 to 1\n",
+        "move 1 from 1 to 2\n"
+    );
+
+    #[test]
+    fn it_parse_cargo() {
+        let actual = parse_cargo(TEST_INPUT, &CrateMover9000).unwrap();
+        let expect = tops(&["C", "M", "Z"]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_parse_cargo2() {
+        let actual = parse_cargo(TEST_INPUT, &CrateMover9001).unwrap();
+        let expect = tops(&["M", "C", "D"]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_parse_cargo_crlf() {
+        let input = TEST_INPUT.replace('\n', "\r\n");
+        assert_eq!(parse_cargo(&input, &CrateMover9000).unwrap(), tops(&["C", "M", "Z"]));
+        assert_eq!(parse_input(&input), parse_input(TEST_INPUT));
+    }
+
+    #[test]
+    fn it_parse_cargo_generated() {
+        let options = generator::GenerateOptions::default();
+        for seed in 0..20 {
+            let puzzle = generator::generate(&options, seed);
+            let input = puzzle.input();
+            assert_eq!(format_top(&parse_cargo(&input, &CrateMover9000).unwrap()), puzzle.expected_9000);
+            assert_eq!(format_top(&parse_cargo(&input, &CrateMover9001).unwrap()), puzzle.expected_9001);
+        }
+    }
+
+    #[test]
+    fn it_parse_cargo_batch_crane() {
+        let actual = parse_cargo(TEST_INPUT, &BatchCrane { capacity: 1 }).unwrap();
+        let expect = tops(&["C", "M", "Z"]);
+        assert_eq!(actual, expect);
+
+        let actual = parse_cargo(TEST_INPUT, &BatchCrane { capacity: 2 }).unwrap();
+        let expect = tops(&["M", "C", "Z"]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_parse_cargo_in_rope_storage() {
+        let cranes: [&dyn Crane; 3] = [&CrateMover9000, &CrateMover9001, &BatchCrane { capacity: 2 }];
+        for crane in cranes {
+            let actual = parse_cargo_in(TEST_INPUT, crane, Storage::Rope);
+            assert_eq!(actual, parse_cargo(TEST_INPUT, crane));
+        }
+        let actual = parse_cargo_in(TEST_INPUT.replace("move 3", "move 4").as_str(), &CrateMover9000, Storage::Rope);
+        let expect = CargoError::EmptyStack { line: 7, stack: 1, wanted: 4, available: 3 };
+        assert_eq!(actual, Err(expect));
+    }
+
+    #[test]
+    fn it_find_instruction_line() {
+        let lines: Vec<_> = TEST_INPUT.split('\n').collect();
+        let actual = find_instruction_line(&lines);
+        let expect = Ok(4);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_build_platform_idxs() {
+        let lines: Vec<_> = TEST_INPUT.split('\n').collect();
+        let actual = build_platform_idxs(lines[3], 4);
+        let expect = Ok(Vec::from([(1, 2), (5, 6), (9, 10)]));
+        assert_eq!(actual, expect);
+
+        let actual = build_platform_idxs("  9   10  11 ", 4);
+        let expect = CargoError::BadFooter { line: 4, column: 3, found: String::from("9"), expected: 1 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = build_platform_idxs(" 1   2   2 ", 2);
+        let expect = CargoError::BadFooter { line: 2, column: 10, found: String::from("2"), expected: 3 };
+        assert_eq!(actual, Err(expect));
+    }
+
+    #[test]
+    fn it_load_platforms() {
+        let levels = Vec::from([    
+            &"[Z] [M] [P]",    
+            &"[N] [C]    ",
+            &"[D]        ",
+        ]);
+        let platform_idxs = Vec::from([(1, 2), (5, 6), (9, 10)]);
+        let actual = load_platforms(levels, platform_idxs).unwrap();
+        let expect = platforms_from(&["ZND", "MC", "P"]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_load_platforms_wide() {
+        let levels = Vec::from([
+            &"[AB] [C]                 [Z]  [Y]",
+            &"      [DEF]                   [X]",
+        ]);
+        let platform_idxs = build_platform_idxs("  1    2   3 4 5 6 7 8 9  10   11", 3).unwrap();
+        let actual = load_platforms(levels, platform_idxs).unwrap();
+        let mut expect = vec![Vec::new(); 11];
+        expect[0] = Vec::from([String::from("AB")]);
+        expect[1] = Vec::from([String::from("C"), String::from("DEF")]);
+        expect[9] = Vec::from([String::from("Z")]);
+        expect[10] = Vec::from([String::from("Y"), String::from("X")]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_parse_cargo_many_stacks() {
+        let input = concat!(
+            "[A]                                     [J] [KL]\n",
+            "[B] [C] [D] [E] [F] [G] [H] [I] [X] [Y] [Z] [MN]\n",
+            " 1   2   3   4   5   6   7   8   9  10  11   12 \n",
+            "\n",
+            "move 2 from 12 to 10\n",
+            "move 1 from 1 to 11\n",
+        );
+        let actual = parse_cargo(input, &CrateMover9001).unwrap();
+        let expect = tops(&[
+            "B", "C", "D", "E", "F", "G", "H", "I", "X", "KL", "A", "",
+        ]);
+        assert_eq!(actual, expect);
+        assert_eq!(format_top(&actual), "B C D E F G H I X KL A");
+        assert_eq!(format_top(&tops(&["", "C", "", "Z"])), "CZ");
+    }
+
+    #[test]
+    fn it_parse_move_instructions() {
+        let lines = [
+            "move 1 from 2 to 1",
+            "move 3 from 1 to 3",
+            "move 2 from 2 to 1",
+            "move 1 from 1 to 2",
+        ].iter();
+        let actual = parse_move_instructions(lines, 6).unwrap();
+        let expect = Vec::from([
+            Move { line: 6, amount: 1, source: 2, target: 1 },
+            Move { line: 7, amount: 3, source: 1, target: 3 },
+            Move { line: 8, amount: 2, source: 2, target: 1 },
+            Move { line: 9, amount: 1, source: 1, target: 2 },
+        ]);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_execute_instructions() {
+        let instructions = Vec::from([
+            Move { line: 6, amount: 1, source: 2, target: 1 },
+            Move { line: 7, amount: 3, source: 1, target: 3 },
+            Move { line: 8, amount: 2, source: 2, target: 1 },
+            Move { line: 9, amount: 1, source: 1, target: 2 },
+        ]);
+        let mut platforms = platforms_from(&["ZN", "MCD", "P"]);
+        execute_instructions(instructions, &mut platforms, &CrateMover9000).unwrap();
+        let expect = platforms_from(&["C", "M", "PDNZ"]);
+        assert_eq!(platforms, expect);
+    }
+
+    #[test]
+    fn it_execute_instructions2() {
+        let instructions = Vec::from([
+            Move { line: 6, amount: 1, source: 2, target: 1 },
+            Move { line: 7, amount: 3, source: 1, target: 3 },
+            Move { line: 8, amount: 2, source: 2, target: 1 },
+            Move { line: 9, amount: 1, source: 1, target: 2 },
+        ]);
+        let mut platforms = platforms_from(&["ZN", "MCD", "P"]);
+        execute_instructions(instructions, &mut platforms, &CrateMover9001).unwrap();
+        let expect = platforms_from(&["M", "C", "PZND"]);
+        assert_eq!(platforms, expect);
+    }
+
+    #[test]
+    fn it_parse_drawing() {
+        let actual = parse_drawing(TEST_INPUT).unwrap();
+        let expect = platforms_from(&["ZN", "MCD", "P"]);
+        assert_eq!(actual, expect);
+
+        let actual = parse_drawing("[A]\n 1 ").unwrap();
+        assert_eq!(actual, platforms_from(&["A"]));
+        let actual = parse_drawing("[A]\r\n 1 \r\n\r\nmove 1 from 1 to 1\r\n").unwrap();
+        assert_eq!(actual, platforms_from(&["A"]));
+        assert_eq!(parse_drawing("\n"), Err(CargoError::MissingFooter));
+    }
+
+    #[test]
+    fn it_reverse_instructions() {
+        let (start, instructions) = parse_input(TEST_INPUT).unwrap();
+        let cranes: [&dyn Crane; 3] = [&CrateMover9000, &CrateMover9001, &BatchCrane { capacity: 2 }];
+        for crane in cranes {
+            let mut platforms = start.clone();
+            execute_instructions(instructions.clone(), &mut platforms, crane).unwrap();
+            reverse_instructions(instructions.clone(), &mut platforms, crane).unwrap();
+            assert_eq!(platforms, start);
+        }
+
+        let mut platforms = platforms_from(&["C", "M", "PDNZ"]);
+        reverse_instructions(instructions.clone(), &mut platforms, &CrateMover9000).unwrap();
+        assert_eq!(platforms, start);
+
+        let mut platforms = platforms_from(&["CM", "", "PDNZ"]);
+        let actual = reverse_instructions(instructions, &mut platforms, &CrateMover9000);
+        let expect = CargoError::EmptyStack { line: 9, stack: 2, wanted: 1, available: 0 };
+        assert_eq!(actual, Err(expect));
+    }
+
+    #[test]
+    fn it_parse_cargo_errors() {
+        let actual = parse_cargo("[A]\n 1 \nmove 1 from 1 to 1", &CrateMover9000);
+        assert_eq!(actual, Err(CargoError::MissingSeparator));
+
+        let actual = parse_cargo("\nmove 1 from 1 to 1", &CrateMover9000);
+        assert_eq!(actual, Err(CargoError::MissingFooter));
+
+        let actual = parse_cargo("[A] [B]\n 1   3 \n\n", &CrateMover9000);
+        let expect = CargoError::BadFooter { line: 2, column: 6, found: String::from("3"), expected: 2 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo("[A]\n a \n\n", &CrateMover9000);
+        let expect = CargoError::BadFooter { line: 2, column: 2, found: String::from("a"), expected: 1 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo("[A] (B)\n 1   2 \n\n", &CrateMover9000);
+        let expect = CargoError::MalformedCrate { line: 1, column: 5, cell: String::from("(B)") };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo("[A] [B\n 1   2 \n\n", &CrateMover9000);
+        let expect = CargoError::MalformedCrate { line: 1, column: 5, cell: String::from("[B") };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo("[A]     [B]\n 1   2 \n\n", &CrateMover9000);
+        let expect = CargoError::MalformedCrate { line: 1, column: 9, cell: String::from("[B]") };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo(TEST_INPUT.replace("to 3", "to 4").as_str(), &CrateMover9000);
+        let expect = CargoError::BadStackIndex { line: 7, stack: 4, stacks: 3 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo(TEST_INPUT.replace("from 2 to 1", "from 0 to 1").as_str(), &CrateMover9000);
+        let expect = CargoError::BadStackIndex { line: 6, stack: 0, stacks: 3 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo(TEST_INPUT.replace("move 3", "move 4").as_str(), &CrateMover9001);
+        let expect = CargoError::EmptyStack { line: 7, stack: 1, wanted: 4, available: 3 };
+        assert_eq!(actual, Err(expect));
+
+        let actual = parse_cargo(TEST_INPUT.replace("move 2", "move 99999999999999999999").as_str(), &CrateMover9000);
+        let expect = CargoError::BadNumber { line: 8, value: String::from("99999999999999999999") };
+        assert_eq!(actual, Err(expect));
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::error::CargoError;
+use crate::{build_platform_idxs, find_instruction_line, load_platforms, parse_move_line, split_lines};
+
+/// Something wrong with one line of a procedure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The drawing itself cannot be read, so moves are not checked.
+    Drawing(CargoError),
+    /// A non-blank line that is not a `move` instruction. The parser skips
+    /// these silently.
+    Unrecognized(String),
+    BadNumber(String),
+    MissingStack { stack: usize, stacks: usize },
+    NotEnoughCrates { stack: usize, wanted: usize, available: usize },
+    SameStack { stack: usize },
+    NoCrates,
+}
+
+impl Issue {
+    /// Warnings leave the procedure runnable, errors do not.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::SameStack { .. } | Issue::NoCrates)
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::Drawing(error) => write!(f, "{}", error),
+            Issue::Unrecognized(text) => write!(f, "not a move instruction: {:?}", text),
+            Issue::BadNumber(value) => write!(f, "{:?} is not a valid number", value),
+            Issue::MissingStack { stack, stacks } =>
+                write!(f, "stack {} does not exist, there are {} stacks", stack, stacks),
+            Issue::NotEnoughCrates { stack, wanted, available } =>
+                write!(f, "moves {} crates from stack {}, which only holds {} at this point", wanted, stack, available),
+            Issue::SameStack { stack } => write!(f, "moves stack {} onto itself", stack),
+            Issue::NoCrates => write!(f, "moves no crates"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub line: usize,
+    pub issue: Issue,
+}
+
+/// Everything found by [`lint`], in line order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintReport {
+    pub findings: Vec<Finding>,
+    pub instructions: usize,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> usize {
+        self.findings.iter().filter(|f| f.issue.is_error()).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.findings.len() - self.errors()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in &self.findings {
+            let severity = if finding.issue.is_error() { "error" } else { "warning" };
+            writeln!(f, "line {}: {}: {}", finding.line, severity, finding.issue)?;
+        }
+        write!(
+            f, "{} errors, {} warnings in {} instructions",
+            self.errors(), self.warnings(), self.instructions,
+        )
+    }
+}
+
+/// Checks a whole puzzle input without moving any crates. Only stack heights
+/// are tracked; a move that wants more crates than are there counts as taking
+/// what is left, so one bad move does not hide the problems after it.
+pub fn lint(input: &str) -> LintReport {
+    let mut report = LintReport {
+        findings: Vec::new(),
+        instructions: 0,
+    };
+    let lines = split_lines(input);
+    let instruction_idx = match find_instruction_line(&lines) {
+        Ok(idx) => idx,
+        Err(error) => {
+            let line = if error == CargoError::MissingFooter { 1 } else { lines.len() };
+            report.findings.push(Finding { line, issue: Issue::Drawing(error) });
+            return report;
+        },
+    };
+    let levels = lines[0..(instruction_idx-1)].iter().rev().collect();
+    let platforms = build_platform_idxs(lines[instruction_idx-1], instruction_idx)
+        .and_then(|platform_idxs| load_platforms(levels, platform_idxs));
+    let mut heights = match platforms {
+        Ok(platforms) => Some(platforms.iter().map(Vec::len).collect::<Vec<_>>()),
+        Err(error) => {
+            let line = match error {
+                CargoError::MalformedCrate { line, .. } | CargoError::BadFooter { line, .. } => line,
+                _ => 1,
+            };
+            report.findings.push(Finding { line, issue: Issue::Drawing(error) });
+            None
+        },
+    };
+
+    for (i, l) in lines.iter().enumerate().skip(instruction_idx + 1) {
+        let line = i + 1;
+        let mut issue = |issue| report.findings.push(Finding { line, issue });
+        let instruction = match parse_move_line(l, line) {
+            None if l.trim().is_empty() => continue,
+            None => {
+                issue(Issue::Unrecognized(l.to_string()));
+                continue;
+            },
+            Some(Err(CargoError::BadNumber { value, .. })) => {
+                issue(Issue::BadNumber(value));
+                continue;
+            },
+            Some(Err(error)) => {
+                issue(Issue::Drawing(error));
+                continue;
+            },
+            Some(Ok(instruction)) => instruction,
+        };
+        report.instructions += 1;
+        if instruction.amount == 0 {
+            issue(Issue::NoCrates);
+        }
+        if instruction.source == instruction.target {
+            issue(Issue::SameStack { stack: instruction.source });
+        }
+        let Some(heights) = heights.as_mut() else {
+            continue;
+        };
+        let stacks = heights.len();
+        let mut valid = true;
+        for stack in [instruction.source, instruction.target] {
+            if stack == 0 || stack > stacks {
+                issue(Issue::MissingStack { stack, stacks });
+                valid = false;
+            }
+        }
+        if !valid {
+            continue;
+        }
+        let available = heights[instruction.source-1];
+        if instruction.amount > available {
+            issue(Issue::NotEnoughCrates { stack: instruction.source, wanted: instruction.amount, available });
+        }
+        let moved = instruction.amount.min(available);
+        heights[instruction.source-1] -= moved;
+        heights[instruction.target-1] += moved;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TEST_INPUT;
+
+    #[test]
+    fn it_accepts_the_example() {
+        let report = lint(TEST_INPUT);
+        assert!(report.is_clean());
+        assert_eq!(report.instructions, 4);
+        assert_eq!(report.to_string(), "0 errors, 0 warnings in 4 instructions");
+        assert_eq!(lint(&TEST_INPUT.replace('\n', "\r\n")), report);
+    }
+
+    #[test]
+    fn it_reports_every_problem() {
+        let input = concat!(
+            "[Z] [M] [P]\n",
+            " 1   2   3 \n",
+            "\n",
+            "move 1 from 2 to 4\n",
+            "mvoe 1 from 2 to 1\n",
+            "move 2 from 1 to 3\n",
+            "move 1 from 3 to 3\n",
+            "move 0 from 0 to 1\n",
+            "move 99999999999999999999 from 1 to 2\n",
+            "move 2 from 3 to 1\n",
+            "move 3 from 1 to 2\n",
+            "please move 1 from 2 to 1 now\n",
+            "move 1 from 2 to 1x\n",
+        );
+        let report = lint(input);
+        let expect = Vec::from([
+            Finding { line: 4, issue: Issue::MissingStack { stack: 4, stacks: 3 } },
+            Finding { line: 5, issue: Issue::Unrecognized(String::from("mvoe 1 from 2 to 1")) },
+            Finding { line: 6, issue: Issue::NotEnoughCrates { stack: 1, wanted: 2, available: 1 } },
+            Finding { line: 7, issue: Issue::SameStack { stack: 3 } },
+            Finding { line: 8, issue: Issue::NoCrates },
+            Finding { line: 8, issue: Issue::MissingStack { stack: 0, stacks: 3 } },
+            Finding { line: 9, issue: Issue::BadNumber(String::from("99999999999999999999")) },
+            Finding { line: 11, issue: Issue::NotEnoughCrates { stack: 1, wanted: 3, available: 2 } },
+            Finding { line: 12, issue: Issue::Unrecognized(String::from("please move 1 from 2 to 1 now")) },
+            Finding { line: 13, issue: Issue::Unrecognized(String::from("move 1 from 2 to 1x")) },
+        ]);
+        assert_eq!(report.findings, expect);
+        assert_eq!(report.instructions, 6);
+        assert_eq!(report.errors(), 8);
+        assert_eq!(report.warnings(), 2);
+        assert!(report.to_string().ends_with("8 errors, 2 warnings in 6 instructions"));
+        assert!(report.to_string().starts_with("line 4: error: stack 4 does not exist"));
+    }
+
+    #[test]
+    fn it_reports_drawing_errors() {
+        let report = lint("[A] {B}\n 1   2 \n\nmove 1 from 1 to 2\nhello\n");
+        let expect = Vec::from([
+            Finding {
+                line: 1,
+                issue: Issue::Drawing(CargoError::MalformedCrate { line: 1, column: 5, cell: String::from("{B}") }),
+            },
+            Finding { line: 5, issue: Issue::Unrecognized(String::from("hello")) },
+        ]);
+        assert_eq!(report.findings, expect);
+
+        let report = lint("[A]\n 1 \nmove 1 from 1 to 1");
+        assert_eq!(report.findings[0].issue, Issue::Drawing(CargoError::MissingSeparator));
+    }
+}
@@ -1,267 +1,265 @@
+use std::env;
 use std::fs;
-use regex::Regex;
-use std::slice::Iter;
+use std::io::{self, BufReader};
+use std::process;
+use std::time::Duration;
+use day5::{format_top, parse_cargo_in, parse_drawing, parse_input, reverse_instructions, top_crates, visual};
+use day5::export::{snapshots, Format};
+use day5::lint::lint;
+use day5::planner::{plan, PlanOptions};
+use day5::provenance::{Position, Provenance};
+use day5::render::render_platforms;
+use day5::repl::Session;
+use day5::rope::Storage;
+use day5::script::run_script;
+use day5::stream::Stream;
+use day5::capacity::{execute_with_capacity, Capacity, Overflow};
+use day5::cost::{measure, CostModel};
+use day5::crane::{BatchCrane, Crane, CrateMover9000, CrateMover9001};
 
-fn main() {
-    let contents = fs::read_to_string("input.txt")
-        .expect("Should have been able to read the file");
-    let input = contents.as_str();
+const USAGE: &str = concat!(
+    "usage: day5 [visual|reverse|plan|lint|trace|script|stream|export|repl|cost|capacity] [--input FILE] [--crane 9000|9001|batch:N] [--delay MS]\n",
+    "            [--target FILE] [--max-height N] [--max-states N] [--crate STACK:HEIGHT] [--format json|csv]\n",
+    "            [--lift-cost N] [--travel-cost N] [--crate-cost N] [--capacity N|N,-,N...]\n",
+    "            [--overflow error|spill|reject] [--storage vec|rope]",
+);
 
-    let score1 = parse_cargo(input);
-    println!("Score1:");
-    println!("{}", score1);
-
-    let score2 = parse_cargo2(input);
-    println!("Score2:");
-    println!("{}", score2);
+struct Options {
+    mode: Option<String>,
+    input: String,
+    crane: Box<dyn Crane>,
+    delay: Duration,
+    target: Option<String>,
+    plan: PlanOptions,
+    origin: Option<Position>,
+    format: Format,
+    cost: CostModel,
+    capacity: Capacity,
+    storage: Storage,
 }
 
-fn parse_cargo(input: &str) -> String {
-    let lines: Vec<_> = input.split('\n').collect();
-    let instruction_idx = find_instruction_line(&lines);
-
-    let platform_line = lines[instruction_idx-1];
-    let platform_idxs = build_platform_idxs(platform_line);
-
-    let levels = lines[0..(instruction_idx-1)].iter().rev().collect();
-    let mut platforms = load_platforms(levels, platform_idxs);
-
-    let instructions = parse_move_instructions(
-        lines[(instruction_idx+1)..lines.len()].iter()
-    );
-    
-    execute_instructions(instructions, &mut platforms);
-
-    let mut result = String::new();
-    for platform in platforms {
-        result.push(platform.last().unwrap().clone());
+fn main() {
+    let options = parse_args(env::args().skip(1).collect()).unwrap_or_else(|e| exit_with(e));
+    if options.mode.as_deref() == Some("stream") {
+        // `--input -` reads the puzzle from stdin.
+        let crane = options.crane.as_ref();
+        let top = if options.input == "-" {
+            run_stream(io::stdin().lock(), crane, options.storage)
+        } else {
+            let file = fs::File::open(&options.input)
+                .unwrap_or_else(|e| exit_with(format!("{}: {}", options.input, e)));
+            run_stream(BufReader::new(file), crane, options.storage)
+        };
+        println!("{}", format_top(&top));
+        return;
     }
-    return result;
-}
-
-fn parse_cargo2(input: &str) -> String {
-    let lines: Vec<_> = input.split('\n').collect();
-    let instruction_idx = find_instruction_line(&lines);
-
-    let platform_line = lines[instruction_idx-1];
-    let platform_idxs = build_platform_idxs(platform_line);
-
-    let levels = lines[0..(instruction_idx-1)].iter().rev().collect();
-    let mut platforms = load_platforms(levels, platform_idxs);
+    let contents = fs::read_to_string(&options.input)
+        .expect("Should have been able to read the file");
+    let input = contents.as_str();
 
-    let instructions = parse_move_instructions(
-        lines[(instruction_idx+1)..lines.len()].iter()
-    );
-    
-    execute_instructions2(instructions, &mut platforms);
+    match options.mode.as_deref() {
+        None => {
+            let score1 = parse_cargo_in(input, &CrateMover9000, options.storage).unwrap_or_else(|e| exit_with(e));
+            println!("Score1:");
+            println!("{}", format_top(&score1));
 
-    let mut result = String::new();
-    for platform in platforms {
-        result.push(platform.last().unwrap().clone());
+            let score2 = parse_cargo_in(input, &CrateMover9001, options.storage).unwrap_or_else(|e| exit_with(e));
+            println!("Score2:");
+            println!("{}", format_top(&score2));
+        },
+        Some("visual") => {
+            let (platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            let top = visual::play(platforms, instructions, options.crane.as_ref(), options.delay)
+                .unwrap_or_else(|e| exit_with(e));
+            match top {
+                Some(top) => println!("{}", format_top(&top)),
+                None => println!("interrupted"),
+            }
+        },
+        Some("reverse") => {
+            let (mut platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            reverse_instructions(instructions, &mut platforms, options.crane.as_ref())
+                .unwrap_or_else(|e| exit_with(e));
+            print!("{}", render_platforms(&platforms).unwrap_or_else(|e| exit_with(e)));
+        },
+        Some("plan") => {
+            let path = options.target.as_deref().unwrap_or_else(|| exit_with(USAGE));
+            let target = fs::read_to_string(path)
+                .expect("Should have been able to read the target file");
+            let start = parse_drawing(input).unwrap_or_else(|e| exit_with(e));
+            let target = parse_drawing(&target).unwrap_or_else(|e| exit_with(e));
+            let moves = plan(&start, &target, options.crane.as_ref(), options.plan)
+                .unwrap_or_else(|e| exit_with(e));
+            for instruction in moves {
+                println!("{}", instruction);
+            }
+        },
+        Some("lint") => {
+            let report = lint(input);
+            println!("{}", report);
+            if report.errors() > 0 {
+                process::exit(1);
+            }
+        },
+        Some("trace") => {
+            let origin = options.origin.unwrap_or_else(|| exit_with(USAGE));
+            let (platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            let provenance = Provenance::track(&platforms, &instructions, options.crane.as_ref())
+                .unwrap_or_else(|e| exit_with(e));
+            let record = provenance.started_at(origin)
+                .unwrap_or_else(|| exit_with("no crate starts at that position"));
+            println!("[{}] starts on stack {} at height {}", record.label, origin.stack, origin.height);
+            for touch in &record.history {
+                println!(
+                    "  step {} (line {}): {} -> stack {} at height {}",
+                    touch.step, touch.instruction.line, touch.instruction,
+                    touch.landed.stack, touch.landed.height,
+                );
+            }
+            println!("[{}] ends on stack {} at height {}", record.label, record.position.stack, record.position.height);
+        },
+        Some("script") => {
+            let platforms = run_script(input, options.crane.as_ref()).unwrap_or_else(|e| exit_with(e));
+            print!("{}", render_platforms(&platforms).unwrap_or_else(|e| exit_with(e)));
+            println!("{}", format_top(&top_crates(&platforms)));
+        },
+        Some("export") => {
+            let (platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            let snapshots = snapshots(platforms, instructions, options.crane.as_ref())
+                .unwrap_or_else(|e| exit_with(e));
+            print!("{}", options.format.write(&snapshots));
+        },
+        Some("repl") => {
+            let platforms = parse_drawing(input).unwrap_or_else(|e| exit_with(e));
+            Session::new(platforms, options.crane.as_ref())
+                .run(io::stdin().lock(), &mut io::stdout())
+                .unwrap_or_else(|e| exit_with(e));
+        },
+        Some("cost") => {
+            let (platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            let mut cranes: Vec<&dyn Crane> = Vec::from([&CrateMover9000 as &dyn Crane, &CrateMover9001]);
+            if cranes.iter().all(|crane| crane.name() != options.crane.name()) {
+                cranes.push(options.crane.as_ref());
+            }
+            let reports: Vec<_> = cranes.into_iter()
+                .map(|crane| measure(&platforms, &instructions, crane, &options.cost).unwrap_or_else(|e| exit_with(e)))
+                .collect();
+            for report in &reports {
+                println!("{}", report);
+            }
+            if let Some(cheapest) = reports.iter().min_by_key(|report| report.total) {
+                println!("cheapest: {}", cheapest.crane);
+            }
+        },
+        Some("capacity") => {
+            let (mut platforms, instructions) = parse_input(input).unwrap_or_else(|e| exit_with(e));
+            let mut capacity = options.capacity;
+            if let [Some(limit)] = capacity.limits[..] {
+                // A single limit applies to every stack.
+                capacity = Capacity::uniform(platforms.len(), limit, capacity.overflow);
+            }
+            let violations = execute_with_capacity(&instructions, &mut platforms, options.crane.as_ref(), &capacity)
+                .unwrap_or_else(|e| exit_with(e));
+            for violation in &violations {
+                println!("{}", violation);
+            }
+            println!("{} violations", violations.len());
+            println!("{}", format_top(&top_crates(&platforms)));
+        },
+        Some(mode) => exit_with(format!("unknown mode {:?}\n{}", mode, USAGE)),
     }
-    return result;
 }
 
-fn execute_instructions(instructions: Vec<(usize, usize, usize)>, platforms: &mut Vec<Vec<char>>) {
-    for (amount, source, target) in instructions {
-        for _ in 0..amount {
-            let cargo = platforms[source-1].pop().unwrap();
-            platforms[target-1].push(cargo);
-        }
-    }
+fn run_stream(reader: impl io::BufRead, crane: &dyn Crane, storage: Storage) -> Vec<Option<String>> {
+    let mut stream = Stream::with_storage(reader, storage).unwrap_or_else(|e| exit_with(e));
+    stream.run(crane).unwrap_or_else(|e| exit_with(e));
+    stream.top_crates()
 }
 
-fn execute_instructions2(instructions: Vec<(usize, usize, usize)>, platforms: &mut Vec<Vec<char>>) {
-    for (amount, source, target) in instructions {
-        let mut crane = Vec::new();
-        for _ in 0..amount {
-            let cargo = platforms[source-1].pop().unwrap();
-            crane.push(cargo);
-        }
-        while !crane.is_empty() {
-            let cargo = crane.pop().unwrap();
-            platforms[target-1].push(cargo);
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        mode: None,
+        input: String::from("input.txt"),
+        crane: Box::new(CrateMover9000),
+        delay: Duration::from_millis(40),
+        target: None,
+        plan: PlanOptions::default(),
+        origin: None,
+        format: Format::Json,
+        cost: CostModel::default(),
+        capacity: Capacity { limits: Vec::new(), overflow: Overflow::Error },
+        storage: Storage::Vec,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value\n{}", arg, USAGE));
+        match arg.as_str() {
+            "--input" => options.input = value()?,
+            "--crane" => options.crane = parse_crane(&value()?)?,
+            "--delay" => options.delay = value()?
+                .parse()
+                .map(Duration::from_millis)
+                .map_err(|_| format!("--delay needs milliseconds\n{}", USAGE))?,
+            "--target" => options.target = Some(value()?),
+            "--max-height" => options.plan.max_height = Some(parse_number(&arg, value()?)?),
+            "--max-states" => options.plan.max_states = parse_number(&arg, value()?)?,
+            "--lift-cost" => options.cost.per_lift = parse_number(&arg, value()?)? as u64,
+            "--travel-cost" => options.cost.per_distance = parse_number(&arg, value()?)? as u64,
+            "--crate-cost" => options.cost.per_crate = parse_number(&arg, value()?)? as u64,
+            "--capacity" => {
+                options.capacity.limits = value()?
+                    .split(',')
+                    .map(|limit| if limit == "-" { Ok(None) } else { parse_number(&arg, limit.to_string()).map(Some) })
+                    .collect::<Result<_, _>>()?;
+            },
+            "--overflow" => {
+                let value = value()?;
+                options.capacity.overflow = Overflow::from_name(&value)
+                    .ok_or(format!("unknown overflow policy {:?}\n{}", value, USAGE))?;
+            },
+            "--storage" => {
+                let value = value()?;
+                options.storage = Storage::from_name(&value)
+                    .ok_or(format!("unknown storage {:?}\n{}", value, USAGE))?;
+            },
+            "--format" => {
+                let value = value()?;
+                options.format = Format::from_name(&value)
+                    .ok_or(format!("unknown format {:?}\n{}", value, USAGE))?;
+            },
+            "--crate" => {
+                let value = value()?;
+                let (stack, height) = value.split_once(':')
+                    .ok_or(format!("--crate needs STACK:HEIGHT\n{}", USAGE))?;
+                options.origin = Some(Position {
+                    stack: parse_number(&arg, stack.to_string())?,
+                    height: parse_number(&arg, height.to_string())?,
+                });
+            },
+            _ if options.mode.is_none() && !arg.starts_with("--") => options.mode = Some(arg),
+            _ => return Err(format!("unexpected argument {:?}\n{}", arg, USAGE)),
         }
     }
+    Ok(options)
 }
 
-fn parse_move_instructions(lines: Iter<&str>) -> Vec<(usize, usize, usize)> {
-    let re = Regex::new(r"\s*move (\d+) from (\d+) to (\d+)\s*").unwrap();
-    let instructions = lines.flat_map(
-        |l| 
-        re.captures(l).map(
-            |cap| (
-                cap[1].parse().unwrap_or(0), 
-                cap[2].parse().unwrap_or(0),
-                cap[3].parse().unwrap_or(0),
-            )
-        )
-    ).collect();
-    return instructions;
+fn parse_number(arg: &str, value: String) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("{} needs a number\n{}", arg, USAGE))
 }
 
-fn load_platforms(levels: Vec<&&str>, platform_idxs: Vec<usize>) -> Vec<Vec<char>> {
-    let mut platforms: Vec<Vec<char>> = vec![Vec::new(); platform_idxs.len()];
-    levels.iter()
-        .for_each(
-            |l| {
-            platform_idxs.iter()
-                .enumerate()
-                .for_each(
-                    |(i, idx)|
-                    match l.chars().nth(idx.clone()) {
-                        Some(c) if c.is_alphabetic() => platforms[i].push(c),
-                        _ => (),
-                    }
-                )
-        });
-    return platforms;
-}
-
-fn find_instruction_line(lines: &Vec<&str>) -> usize {
-    return lines.iter()
-        .enumerate()
-        .find(|(_, l)| l.len() == 0)
-        .map(|(i, _)| i)
-        .unwrap_or(0);
-}
-
-fn build_platform_idxs(platform_line: &str) -> Vec<usize> {
-    return platform_line.chars()
-        .into_iter()
-        .enumerate()
-        .filter(
-            |(_, c)| !c.is_whitespace()
-        )
-        .map(
-            |(i, _)| i
-        )
-        .collect();
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TEST_INPUT: &str = concat!(
-        "    [D]    \n",
-        "[N] [C]    \n",
-        "[Z] [M] [P]\n",
-        " 1   2   3 \n",
-        "\n",
-        "move 1 from 2 to 1\n",
-        "move 3 from 1 to 3\n",
-        "move 2 from 2 to 1\n",
-        "move 1 from 1 to 2\n"
-    );
-
-    #[test]
-    fn it_parse_cargo() {
-        let actual = parse_cargo(TEST_INPUT);
-        let expect = String::from("CMZ");
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_parse_cargo2() {
-        let actual = parse_cargo2(TEST_INPUT);
-        let expect = String::from("MCD");
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_find_instruction_line() {
-        let lines: Vec<_> = TEST_INPUT.split('\n').collect();
-        let actual = find_instruction_line(&lines);
-        let expect = 4;
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_build_platform_idxs() {
-        let lines: Vec<_> = TEST_INPUT.split('\n').collect();
-        let actual = build_platform_idxs(lines[3]);
-        let expect = Vec::from([1, 5, 9]);
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_load_platforms() {
-        let levels = Vec::from([    
-            &"[Z] [M] [P]",    
-            &"[N] [C]    ",
-            &"[D]        ",
-        ]);
-        let platform_idxs = Vec::from([1, 5, 9]); 
-        let actual = load_platforms(levels, platform_idxs);
-        let expect = Vec::from([
-            Vec::from(['Z', 'N', 'D']),
-            Vec::from(['M', 'C']),
-            Vec::from(['P']),    
-        ]);
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_parse_move_instructions() {
-        let lines = [
-            "move 1 from 2 to 1",
-            "move 3 from 1 to 3",
-            "move 2 from 2 to 1",
-            "move 1 from 1 to 2",
-        ].iter();
-        let actual = parse_move_instructions(lines);
-        let expect = Vec::from([
-            (1, 2, 1),
-            (3, 1, 3),
-            (2, 2, 1),
-            (1, 1, 2),
-        ]);
-        assert_eq!(actual, expect);
-    }
-
-    #[test]
-    fn it_execute_instructions() {
-        let instructions = Vec::from([
-            (1, 2, 1),
-            (3, 1, 3),
-            (2, 2, 1),
-            (1, 1, 2),
-        ]);
-        let mut platforms = Vec::from([
-            Vec::from(['Z', 'N']),
-            Vec::from(['M', 'C', 'D']),
-            Vec::from(['P']),
-        ]);
-        execute_instructions(instructions, &mut platforms);
-        let expect = Vec::from([
-            Vec::from(['C']),
-            Vec::from(['M']),
-            Vec::from(['P', 'D', 'N', 'Z']),
-        ]);
-        assert_eq!(platforms, expect);
-    }
-
-    #[test]
-    fn it_execute_instructions2() {
-        let instructions = Vec::from([
-            (1, 2, 1),
-            (3, 1, 3),
-            (2, 2, 1),
-            (1, 1, 2),
-        ]);
-        let mut platforms = Vec::from([
-            Vec::from(['Z', 'N']),
-            Vec::from(['M', 'C', 'D']),
-            Vec::from(['P']),
-        ]);
-        execute_instructions2(instructions, &mut platforms);
-        let expect = Vec::from([
-            Vec::from(['M']),
-            Vec::from(['C']),
-            Vec::from(['P', 'Z', 'N', 'D']),
-        ]);
-        assert_eq!(platforms, expect);
+fn parse_crane(name: &str) -> Result<Box<dyn Crane>, String> {
+    match name.split_once(':') {
+        None if name == "9000" => Ok(Box::new(CrateMover9000)),
+        None if name == "9001" => Ok(Box::new(CrateMover9001)),
+        Some(("batch", capacity)) => match capacity.parse() {
+            Ok(capacity) if capacity > 0 => Ok(Box::new(BatchCrane { capacity })),
+            _ => Err(format!("batch capacity needs to be at least 1, got {:?}", capacity)),
+        },
+        _ => Err(format!("unknown crane {:?}\n{}", name, USAGE)),
     }
+}
 
+fn exit_with(error: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1);
 }
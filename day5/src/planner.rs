@@ -0,0 +1,201 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::{execute_move, Move};
+
+/// Limits for [`plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanOptions {
+    /// No stack may grow taller than this, if set.
+    pub max_height: Option<usize>,
+    /// How many distinct arrangements the search may visit before giving up.
+    pub max_states: usize,
+}
+
+impl Default for PlanOptions {
+    fn default() -> Self {
+        PlanOptions {
+            max_height: None,
+            max_states: 200_000,
+        }
+    }
+}
+
+/// Searches breadth first for a shortest procedure that turns `start` into
+/// `target` with `crane`. The moves are numbered from line 1 and print as
+/// `move N from A to B`, so they can be fed straight back to the parser.
+///
+/// Every arrangement is stored once, in `order`, which doubles as the queue;
+/// `seen` only maps arrangement hashes to their places in it.
+pub fn plan(start: &[Vec<String>], target: &[Vec<String>], crane: &dyn Crane, options: PlanOptions) -> Result<Vec<Move>, CargoError> {
+    if !same_crates(start, target) {
+        return Err(CargoError::MismatchedCrates);
+    }
+    let mut order = Vec::from([start.to_vec()]);
+    let mut parents: Vec<Option<(usize, Move)>> = Vec::from([None]);
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::from([(state_hash(start), Vec::from([0]))]);
+
+    let mut current = 0;
+    while current < order.len() {
+        if order[current] == target {
+            return Ok(trace(&parents, current));
+        }
+        for (source, target_stack, amount) in candidate_moves(&order[current], options.max_height) {
+            let instruction = Move { line: 0, amount, source, target: target_stack };
+            let mut next = order[current].clone();
+            execute_move(&instruction, &mut next, crane)?;
+            let places = seen.entry(state_hash(&next)).or_default();
+            if places.iter().any(|place| order[*place] == next) {
+                continue;
+            }
+            if next == target {
+                parents.push(Some((current, instruction)));
+                return Ok(trace(&parents, parents.len() - 1));
+            }
+            if order.len() >= options.max_states {
+                return Err(CargoError::PlanNotFound { explored: order.len() });
+            }
+            places.push(order.len());
+            order.push(next);
+            parents.push(Some((current, instruction)));
+        }
+        current += 1;
+    }
+    Err(CargoError::PlanNotFound { explored: order.len() })
+}
+
+fn state_hash(platforms: &[Vec<String>]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    platforms.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Every `(source, target, amount)` allowed on `platforms`, stacks from 1.
+fn candidate_moves(platforms: &[Vec<String>], max_height: Option<usize>) -> Vec<(usize, usize, usize)> {
+    let mut moves = Vec::new();
+    for (source, from) in platforms.iter().enumerate() {
+        for (target, to) in platforms.iter().enumerate() {
+            if source == target {
+                continue;
+            }
+            let room = max_height.map_or(from.len(), |max| max.saturating_sub(to.len()));
+            for amount in 1..=from.len().min(room) {
+                moves.push((source + 1, target + 1, amount));
+            }
+        }
+    }
+    moves
+}
+
+/// Follows the parent links back from `end` to the start.
+fn trace(parents: &[Option<(usize, Move)>], end: usize) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let mut current = end;
+    while let Some((previous, instruction)) = parents[current] {
+        moves.push(instruction);
+        current = previous;
+    }
+    moves.reverse();
+    for (i, instruction) in moves.iter_mut().enumerate() {
+        instruction.line = i + 1;
+    }
+    moves
+}
+
+fn same_crates(start: &[Vec<String>], target: &[Vec<String>]) -> bool {
+    start.len() == target.len() && sorted_labels(start) == sorted_labels(target)
+}
+
+fn sorted_labels(platforms: &[Vec<String>]) -> Vec<&String> {
+    let mut labels: Vec<_> = platforms.iter().flatten().collect();
+    labels.sort();
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::platforms_from;
+    use crate::{execute_instructions, parse_move_instructions};
+
+    #[test]
+    fn it_plans_the_example() {
+        let start = platforms_from(&["ZN", "MCD", "P"]);
+        let target = platforms_from(&["C", "M", "PDNZ"]);
+        let moves = plan(&start, &target, &CrateMover9000, PlanOptions::default()).unwrap();
+        assert!(moves.len() <= 4);
+
+        let lines: Vec<_> = moves.iter().map(|m| m.to_string()).collect();
+        let lines: Vec<_> = lines.iter().map(String::as_str).collect();
+        let parsed = parse_move_instructions(lines.iter(), 1).unwrap();
+        assert_eq!(parsed, moves);
+
+        let mut platforms = start.clone();
+        execute_instructions(parsed, &mut platforms, &CrateMover9000).unwrap();
+        assert_eq!(platforms, target);
+    }
+
+    #[test]
+    fn it_uses_the_crane_model() {
+        let start = platforms_from(&["ABC", "", ""]);
+        let target = platforms_from(&["", "ABC", ""]);
+        let moves = plan(&start, &target, &CrateMover9001, PlanOptions::default()).unwrap();
+        assert_eq!(moves, Vec::from([Move { line: 1, amount: 3, source: 1, target: 2 }]));
+
+        let moves = plan(&start, &target, &CrateMover9000, PlanOptions::default()).unwrap();
+        let expect = Vec::from([
+            Move { line: 1, amount: 3, source: 1, target: 3 },
+            Move { line: 2, amount: 3, source: 3, target: 2 },
+        ]);
+        assert_eq!(moves, expect);
+    }
+
+    #[test]
+    fn it_respects_max_height() {
+        let start = platforms_from(&["AB", "C", ""]);
+        let target = platforms_from(&["C", "", "AB"]);
+        let options = PlanOptions { max_height: Some(2), ..PlanOptions::default() };
+        let moves = plan(&start, &target, &CrateMover9000, options).unwrap();
+
+        let mut platforms = start.clone();
+        for instruction in &moves {
+            execute_move(instruction, &mut platforms, &CrateMover9000).unwrap();
+            assert!(platforms.iter().all(|p| p.len() <= 2));
+        }
+        assert_eq!(platforms, target);
+    }
+
+    #[test]
+    fn it_reports_impossible_targets() {
+        let start = platforms_from(&["AB", ""]);
+        let actual = plan(&start, &platforms_from(&["A", "C"]), &CrateMover9000, PlanOptions::default());
+        assert_eq!(actual, Err(CargoError::MismatchedCrates));
+
+        let options = PlanOptions { max_height: Some(1), ..PlanOptions::default() };
+        let actual = plan(&start, &platforms_from(&["", "AB"]), &CrateMover9000, options);
+        assert_eq!(actual, Err(CargoError::PlanNotFound { explored: 2 }));
+
+        let start = platforms_from(&["ABCDEFGH", "", ""]);
+        let target = platforms_from(&["", "", "HGFEDCBA"]);
+        let options = PlanOptions { max_states: 10, ..PlanOptions::default() };
+        let actual = plan(&start, &target, &CrateMover9001, options);
+        assert_eq!(actual, Err(CargoError::PlanNotFound { explored: 10 }));
+    }
+
+    #[test]
+    fn it_finds_a_target_at_the_state_limit() {
+        let start = platforms_from(&["A", "B"]);
+        let target = platforms_from(&["", "BA"]);
+        let options = PlanOptions { max_states: 2, ..PlanOptions::default() };
+        let moves = plan(&start, &target, &CrateMover9001, options).unwrap();
+        assert_eq!(moves, Vec::from([Move { line: 1, amount: 1, source: 1, target: 2 }]));
+
+        let options = PlanOptions { max_states: 1, ..PlanOptions::default() };
+        let moves = plan(&start, &target, &CrateMover9001, options).unwrap();
+        assert_eq!(moves.len(), 1);
+    }
+}
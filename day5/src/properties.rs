@@ -0,0 +1,102 @@
+//! Randomized checks of invariants every crane and executor must keep. Each
+//! case comes from a fixed seed, so a failure names the seed to replay.
+
+use crate::crane::{BatchCrane, Crane, CrateMover9000, CrateMover9001};
+use crate::generator::{generate, GenerateOptions, Puzzle};
+use crate::rng::Rng;
+use crate::{build_platform_idxs, execute_move, load_platforms, reverse_instructions, split_lines};
+
+const CASES: u64 = 200;
+
+/// A puzzle of random shape for `seed`.
+fn random_puzzle(seed: u64) -> Puzzle {
+    let mut rng = Rng::new(seed);
+    let options = GenerateOptions {
+        stacks: rng.between(1, 12),
+        crates: rng.between(0, 60),
+        moves: rng.between(0, 40),
+        max_amount: if rng.below(2) == 0 { None } else { Some(rng.between(1, 5)) },
+        label_width: rng.between(1, 3),
+    };
+    generate(&options, seed)
+}
+
+fn sorted_labels(platforms: &[Vec<String>]) -> Vec<String> {
+    let mut labels: Vec<_> = platforms.iter().flatten().cloned().collect();
+    labels.sort();
+    labels
+}
+
+fn cranes(seed: u64) -> [Box<dyn Crane>; 3] {
+    let capacity = Rng::new(seed).between(1, 4);
+    [Box::new(CrateMover9000), Box::new(CrateMover9001), Box::new(BatchCrane { capacity })]
+}
+
+#[test]
+fn it_loads_every_crate_of_a_drawing() {
+    for seed in 0..CASES {
+        let puzzle = random_puzzle(seed);
+        let input = puzzle.input();
+        let lines = split_lines(&input);
+        let footer = lines.iter().position(|l| l.is_empty()).unwrap() - 1;
+        let levels = lines[0..footer].iter().rev().collect();
+        let platforms = load_platforms(levels, build_platform_idxs(lines[footer], footer + 1).unwrap()).unwrap();
+        assert_eq!(platforms, puzzle.platforms, "seed {}", seed);
+    }
+}
+
+#[test]
+fn it_conserves_crates() {
+    for seed in 0..CASES {
+        let puzzle = random_puzzle(seed);
+        let expect = sorted_labels(&puzzle.platforms);
+        for crane in cranes(seed) {
+            let mut platforms = puzzle.platforms.clone();
+            for instruction in &puzzle.instructions {
+                execute_move(instruction, &mut platforms, crane.as_ref()).unwrap();
+                assert_eq!(platforms.len(), puzzle.platforms.len(), "seed {}", seed);
+                assert_eq!(platforms.iter().map(Vec::len).sum::<usize>(), expect.len(), "seed {}", seed);
+                assert_eq!(sorted_labels(&platforms), expect, "seed {}, {}", seed, crane.name());
+            }
+        }
+    }
+}
+
+#[test]
+fn it_moves_single_crates_the_same_with_every_crane() {
+    for seed in 0..CASES {
+        let puzzle = random_puzzle(seed);
+        let mut platforms = puzzle.platforms.clone();
+        for instruction in &puzzle.instructions {
+            let mut single = *instruction;
+            // The states drift apart from the puzzle's, so the source may be empty.
+            single.amount = platforms[single.source-1].len().min(1);
+            let results: Vec<_> = cranes(seed).iter()
+                .map(
+                    |crane| {
+                        let mut after = platforms.clone();
+                        execute_move(&single, &mut after, crane.as_ref()).unwrap();
+                        after
+                    }
+                )
+                .collect();
+            assert!(results.windows(2).all(|pair| pair[0] == pair[1]), "seed {}, line {}", seed, single.line);
+            platforms = results.into_iter().next().unwrap();
+        }
+    }
+}
+
+#[test]
+fn it_reverses_back_to_the_start() {
+    for seed in 0..CASES {
+        let puzzle = random_puzzle(seed);
+        for crane in cranes(seed) {
+            let mut platforms = puzzle.platforms.clone();
+            for instruction in &puzzle.instructions {
+                execute_move(instruction, &mut platforms, crane.as_ref()).unwrap();
+            }
+            reverse_instructions(puzzle.instructions.clone(), &mut platforms, crane.as_ref()).unwrap();
+            assert_eq!(platforms, puzzle.platforms, "seed {}, {}", seed, crane.name());
+        }
+    }
+}
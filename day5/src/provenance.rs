@@ -0,0 +1,181 @@
+use crate::crane::{apply_lift, Crane};
+use crate::error::CargoError;
+use crate::{check_move_with, Move};
+
+/// Where a crate sits: stack number and height from the bottom, both from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub stack: usize,
+    pub height: usize,
+}
+
+/// One move that carried a crate, and where the crate landed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Touch {
+    pub step: usize,
+    pub instruction: Move,
+    pub landed: Position,
+}
+
+/// The full story of one crate through a procedure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateRecord {
+    pub id: usize,
+    pub label: String,
+    pub origin: Position,
+    pub history: Vec<Touch>,
+    pub position: Position,
+}
+
+/// Runs a procedure on crate ids instead of labels, so crates with the same
+/// label stay apart, and records every move each crate takes part in.
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    crates: Vec<CrateRecord>,
+    platforms: Vec<Vec<usize>>,
+}
+
+impl Provenance {
+    pub fn track(platforms: &[Vec<String>], instructions: &[Move], crane: &dyn Crane) -> Result<Self, CargoError> {
+        let mut crates = Vec::new();
+        let mut ids = Vec::new();
+        for (stack, platform) in platforms.iter().enumerate() {
+            let mut stack_ids = Vec::new();
+            for (height, label) in platform.iter().enumerate() {
+                let origin = Position { stack: stack + 1, height: height + 1 };
+                stack_ids.push(crates.len());
+                crates.push(CrateRecord {
+                    id: crates.len(),
+                    label: label.clone(),
+                    origin,
+                    history: Vec::new(),
+                    position: origin,
+                });
+            }
+            ids.push(stack_ids);
+        }
+
+        let mut provenance = Provenance { crates, platforms: ids };
+        for (step, instruction) in instructions.iter().enumerate() {
+            provenance.apply(step + 1, instruction, crane)?;
+        }
+        Ok(provenance)
+    }
+
+    fn apply(&mut self, step: usize, instruction: &Move, crane: &dyn Crane) -> Result<(), CargoError> {
+        let platforms = &mut self.platforms;
+        check_move_with(instruction, platforms.len(), |i| platforms[i].len())?;
+        if instruction.source == instruction.target {
+            // Nothing is carried, so no crate has a move to record.
+            return Ok(());
+        }
+        for lift in crane.lifts(instruction.amount) {
+            apply_lift(platforms, instruction.source-1, instruction.target-1, lift);
+        }
+        let target = &platforms[instruction.target-1];
+        for height in (target.len() - instruction.amount)..target.len() {
+            let landed = Position { stack: instruction.target, height: height + 1 };
+            let record = &mut self.crates[target[height]];
+            record.history.push(Touch { step, instruction: *instruction, landed });
+            record.position = landed;
+        }
+        Ok(())
+    }
+
+    /// Every crate, ordered by id, which counts up stack by stack from the
+    /// bottom of the starting arrangement.
+    pub fn crates(&self) -> &[CrateRecord] {
+        &self.crates
+    }
+
+    /// The crate that started at `position`.
+    pub fn started_at(&self, position: Position) -> Option<&CrateRecord> {
+        self.crates.iter().find(|record| record.origin == position)
+    }
+
+    /// The crate that ended up at `position`.
+    pub fn ended_at(&self, position: Position) -> Option<&CrateRecord> {
+        let id = self.platforms.get(position.stack.checked_sub(1)?)?
+            .get(position.height.checked_sub(1)?)?;
+        Some(&self.crates[*id])
+    }
+
+    /// The final arrangement as crate ids, bottom to top.
+    pub fn platforms(&self) -> &[Vec<usize>] {
+        &self.platforms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::{platforms_from, TEST_INPUT};
+    use crate::{execute_instructions, parse_input};
+
+    #[test]
+    fn it_tracks_history() {
+        let (platforms, instructions) = parse_input(TEST_INPUT).unwrap();
+        let provenance = Provenance::track(&platforms, &instructions, &CrateMover9000).unwrap();
+
+        let d = provenance.started_at(Position { stack: 2, height: 3 }).unwrap();
+        assert_eq!(d.label, "D");
+        let steps: Vec<_> = d.history.iter().map(|touch| (touch.step, touch.landed)).collect();
+        let expect = Vec::from([
+            (1, Position { stack: 1, height: 3 }),
+            (2, Position { stack: 3, height: 2 }),
+        ]);
+        assert_eq!(steps, expect);
+        assert_eq!(d.position, Position { stack: 3, height: 2 });
+        assert_eq!(d.history[1].instruction.line, 7);
+
+        let p = provenance.started_at(Position { stack: 3, height: 1 }).unwrap();
+        assert!(p.history.is_empty());
+        assert_eq!(p.position, p.origin);
+
+        let top = provenance.ended_at(Position { stack: 3, height: 4 }).unwrap();
+        assert_eq!(top.label, "Z");
+        assert_eq!(top.origin, Position { stack: 1, height: 1 });
+        assert!(provenance.ended_at(Position { stack: 1, height: 2 }).is_none());
+        assert!(provenance.ended_at(Position { stack: 0, height: 1 }).is_none());
+    }
+
+    #[test]
+    fn it_keeps_identical_labels_apart() {
+        let platforms = platforms_from(&["AA", "A"]);
+        let instructions = Vec::from([Move { line: 1, amount: 2, source: 1, target: 2 }]);
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let provenance = Provenance::track(&platforms, &instructions, crane).unwrap();
+            let mut expect = platforms.clone();
+            execute_instructions(instructions.clone(), &mut expect, crane).unwrap();
+            let labels: Vec<Vec<String>> = provenance.platforms().iter()
+                .map(|stack| stack.iter().map(|id| provenance.crates()[*id].label.clone()).collect())
+                .collect();
+            assert_eq!(labels, expect);
+        }
+        let provenance = Provenance::track(&platforms, &instructions, &CrateMover9000).unwrap();
+        assert_eq!(provenance.platforms(), &[Vec::new(), Vec::from([2, 1, 0])]);
+        let provenance = Provenance::track(&platforms, &instructions, &CrateMover9001).unwrap();
+        assert_eq!(provenance.platforms(), &[Vec::new(), Vec::from([2, 0, 1])]);
+    }
+
+    #[test]
+    fn it_skips_moves_onto_the_same_stack() {
+        let platforms = platforms_from(&["AB", "C"]);
+        let instructions = Vec::from([
+            Move { line: 1, amount: 2, source: 1, target: 1 },
+            Move { line: 2, amount: 1, source: 1, target: 2 },
+        ]);
+        let provenance = Provenance::track(&platforms, &instructions, &CrateMover9000).unwrap();
+        let a = provenance.started_at(Position { stack: 1, height: 1 }).unwrap();
+        assert!(a.history.is_empty());
+        let b = provenance.started_at(Position { stack: 1, height: 2 }).unwrap();
+        let steps: Vec<_> = b.history.iter().map(|touch| touch.step).collect();
+        assert_eq!(steps, Vec::from([2]));
+        assert_eq!(b.position, Position { stack: 2, height: 2 });
+
+        let instructions = [Move { line: 1, amount: 3, source: 1, target: 1 }];
+        let actual = Provenance::track(&platforms, &instructions, &CrateMover9000).map(|_| ());
+        assert_eq!(actual, Err(CargoError::EmptyStack { line: 1, stack: 1, wanted: 3, available: 2 }));
+    }
+}
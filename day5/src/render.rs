@@ -0,0 +1,130 @@
+use crate::error::CargoError;
+use crate::Move;
+
+/// Width of every column in a drawing of `platforms`: wide enough for the
+/// longest `[label]` and the longest stack number, and at least the usual 3.
+pub fn cell_width(platforms: &[Vec<String>]) -> usize {
+    let label = platforms.iter()
+        .flatten()
+        .map(|label| label.chars().count() + 2)
+        .max()
+        .unwrap_or(0);
+    let number = platforms.len().to_string().len();
+    label.max(number).max(3)
+}
+
+/// Draws the platforms the way the puzzle input does: crate rows from the
+/// top down, every row padded to the full width, then the stack number
+/// footer. Crates and numbers are centred in their column so the parser
+/// matches them up again. Each line ends with a newline. Labels the parser
+/// could not read back fail with [`CargoError::BadLabel`].
+pub fn render_platforms(platforms: &[Vec<String>]) -> Result<String, CargoError> {
+    let unreadable = |label: &&String| label.is_empty() || label.contains(|c: char| c.is_whitespace() || c == ']');
+    if let Some(label) = platforms.iter().flatten().find(unreadable) {
+        return Err(CargoError::BadLabel { label: label.clone() });
+    }
+    let width = cell_width(platforms);
+    let height = platforms.iter().map(Vec::len).max().unwrap_or(0);
+    let mut drawing = String::new();
+    for level in (0..height).rev() {
+        let cells: Vec<_> = platforms.iter()
+            .map(
+                |platform| match platform.get(level) {
+                    Some(label) => format!("{:^width$}", format!("[{}]", label)),
+                    None => " ".repeat(width),
+                }
+            )
+            .collect();
+        drawing.push_str(&cells.join(" "));
+        drawing.push('\n');
+    }
+    let footer: Vec<_> = (1..=platforms.len())
+        .map(|i| format!("{:^width$}", i))
+        .collect();
+    drawing.push_str(&footer.join(" "));
+    drawing.push('\n');
+    Ok(drawing)
+}
+
+/// Renders a complete puzzle input: the drawing, a blank line and the procedure.
+pub fn render_input(platforms: &[Vec<String>], instructions: &[Move]) -> Result<String, CargoError> {
+    let mut input = render_platforms(platforms)?;
+    input.push('\n');
+    for instruction in instructions {
+        input.push_str(&instruction.to_string());
+        input.push('\n');
+    }
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+    use crate::tests::{platforms_from, TEST_INPUT};
+
+    #[test]
+    fn it_render_input() {
+        let (platforms, instructions) = parse_input(TEST_INPUT).unwrap();
+        let actual = render_input(&platforms, &instructions).unwrap();
+        assert_eq!(actual, TEST_INPUT);
+    }
+
+    #[test]
+    fn it_render_platforms() {
+        let platforms = platforms_from(&["", "M", "PDNZ"]);
+        let actual = render_platforms(&platforms).unwrap();
+        let expect = concat!(
+            "        [Z]\n",
+            "        [N]\n",
+            "        [D]\n",
+            "    [M] [P]\n",
+            " 1   2   3 \n",
+        );
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_render_platforms_wide() {
+        let mut platforms: Vec<Vec<String>> = vec![Vec::new(); 10];
+        platforms[0].push(String::from("AB"));
+        platforms[9].push(String::from("C"));
+        let actual = render_platforms(&platforms).unwrap();
+        let expect = concat!(
+            "[AB]                                         [C] \n",
+            " 1    2    3    4    5    6    7    8    9    10 \n",
+        );
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let cases = [
+            platforms_from(&["A"]),
+            platforms_from(&["", ""]),
+            platforms_from(&["ZND", "", "P", "QRSTU"]),
+            (0..9).map(|i| vec![String::from("X"); i]).collect(),
+            (0..12).map(|i| vec![String::from("AB"); i % 3]).collect(),
+            Vec::from([
+                Vec::from([String::from("LONG"), String::from("A")]),
+                Vec::from([String::from("Ü"), String::from("[A")]),
+            ]),
+        ];
+        for platforms in cases {
+            let instructions = Vec::from([
+                Move { line: 0, amount: 1, source: 1, target: platforms.len() },
+            ]);
+            let input = render_input(&platforms, &instructions).unwrap();
+            let (actual, moves) = parse_input(&input).unwrap();
+            assert_eq!(actual, platforms);
+            assert_eq!(moves[0].amount, 1);
+            assert_eq!(moves[0].target, platforms.len());
+        }
+
+        for label in ["A]", "A B", "A\tB", ""] {
+            let platforms = Vec::from([Vec::from([String::from("Z"), String::from(label)])]);
+            let actual = render_input(&platforms, &[]);
+            assert_eq!(actual, Err(CargoError::BadLabel { label: String::from(label) }));
+        }
+    }
+}
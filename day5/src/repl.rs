@@ -0,0 +1,190 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::render::{render_input, render_platforms};
+use crate::{execute_move, format_top, parse_drawing, parse_move_line, reverse_move, top_crates, Move};
+
+const HELP: &str = concat!(
+    "move N from A to B   move crates with the crane\n",
+    "undo / redo          take back the last move or do it again\n",
+    "show                 draw the platforms\n",
+    "top                  print the top crates\n",
+    "save FILE            write the platforms as a drawing\n",
+    "load FILE            read a drawing, forgetting the history\n",
+    "quit                 leave\n",
+);
+
+/// What the loop should do after a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Print(String),
+    Quit,
+}
+
+/// Platforms an operator works on by hand. Moves are undone with
+/// [`reverse_move`], so undo works for every crane without keeping copies.
+pub struct Session<'a> {
+    crane: &'a dyn Crane,
+    platforms: Vec<Vec<String>>,
+    done: Vec<Move>,
+    undone: Vec<Move>,
+    commands: usize,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(platforms: Vec<Vec<String>>, crane: &'a dyn Crane) -> Self {
+        Session {
+            crane,
+            platforms,
+            done: Vec::new(),
+            undone: Vec::new(),
+            commands: 0,
+        }
+    }
+
+    pub fn platforms(&self) -> &[Vec<String>] {
+        &self.platforms
+    }
+
+    /// Runs one command line. Errors are meant for the operator and leave
+    /// the platforms untouched.
+    pub fn execute(&mut self, l: &str) -> Result<Reply, String> {
+        self.commands += 1;
+        let (command, argument) = match l.trim().split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (l.trim(), ""),
+        };
+        let print = |text: String| Ok(Reply::Print(text));
+        match (command, argument) {
+            ("", _) => print(String::new()),
+            ("move", _) => {
+                let instruction = parse_move_line(l, self.commands)
+                    .ok_or("usage: move N from A to B")?
+                    .map_err(command_error)?;
+                execute_move(&instruction, &mut self.platforms, self.crane).map_err(command_error)?;
+                self.done.push(instruction);
+                self.undone.clear();
+                print(String::new())
+            },
+            ("undo", "") => {
+                let instruction = self.done.pop().ok_or("nothing to undo")?;
+                reverse_move(&instruction, &mut self.platforms, self.crane).map_err(command_error)?;
+                self.undone.push(instruction);
+                print(format!("undid {}\n", instruction))
+            },
+            ("redo", "") => {
+                let instruction = self.undone.pop().ok_or("nothing to redo")?;
+                execute_move(&instruction, &mut self.platforms, self.crane).map_err(command_error)?;
+                self.done.push(instruction);
+                print(format!("redid {}\n", instruction))
+            },
+            ("show", "") => print(render_platforms(&self.platforms).map_err(|e| e.to_string())?),
+            ("top", "") => print(format!("{}\n", format_top(&top_crates(&self.platforms)))),
+            ("save", path) if !path.is_empty() => {
+                let drawing = render_input(&self.platforms, &[]).map_err(|e| e.to_string())?;
+                fs::write(path, drawing).map_err(|e| format!("{}: {}", path, e))?;
+                print(format!("saved {}\n", path))
+            },
+            ("load", path) if !path.is_empty() => {
+                let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+                self.platforms = parse_drawing(&contents).map_err(|e| format!("{}: {}", path, e))?;
+                self.done.clear();
+                self.undone.clear();
+                print(format!("loaded {}\n", path))
+            },
+            ("help", "") => print(String::from(HELP)),
+            ("quit" | "exit", "") => Ok(Reply::Quit),
+            _ => Err(format!("unknown command {:?}, try help", l.trim())),
+        }
+    }
+
+    /// Reads commands from `input` until it ends or the operator quits,
+    /// printing a prompt before each one.
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        write!(output, "> ")?;
+        output.flush()?;
+        for l in input.lines() {
+            match self.execute(&l?) {
+                Ok(Reply::Print(text)) => write!(output, "{}", text)?,
+                Ok(Reply::Quit) => return Ok(()),
+                Err(message) => writeln!(output, "error: {}", message)?,
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+/// Session errors carry the number of the command that caused them where
+/// the parser would name a line.
+fn command_error(error: CargoError) -> String {
+    match error.location() {
+        Some((command, _)) => format!("command {}: {}", command, error.message()),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::Cursor;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::platforms_from;
+
+    #[test]
+    fn it_moves_and_undoes() {
+        let mut session = Session::new(platforms_from(&["ZN", "MCD", "P"]), &CrateMover9001);
+        let print = |text: &str| Ok(Reply::Print(String::from(text)));
+        assert_eq!(session.execute("move 2 from 2 to 1"), print(""));
+        assert_eq!(session.execute("top"), print("DMP\n"));
+        assert_eq!(session.execute("move 1 from 3 to 2"), print(""));
+        assert_eq!(session.execute("undo"), print("undid move 1 from 3 to 2\n"));
+        assert_eq!(session.execute("undo"), print("undid move 2 from 2 to 1\n"));
+        assert_eq!(session.platforms(), platforms_from(&["ZN", "MCD", "P"]));
+        assert_eq!(session.execute("undo"), Err(String::from("nothing to undo")));
+        assert_eq!(session.execute("redo"), print("redid move 2 from 2 to 1\n"));
+        assert_eq!(session.platforms(), platforms_from(&["ZNCD", "M", "P"]));
+
+        assert_eq!(session.execute("move 1 from 3 to 1"), print(""));
+        assert_eq!(session.execute("redo"), Err(String::from("nothing to redo")));
+        assert_eq!(session.execute("move 5 from 2 to 1"), Err(String::from(
+            "command 10: cannot move 5 crates from stack 2, it only holds 1",
+        )));
+        assert_eq!(session.execute("move crates"), Err(String::from("usage: move N from A to B")));
+        assert_eq!(session.execute("move 1 from 4 to 1"), Err(String::from(
+            "command 12: stack 4 does not exist, there are 3 stacks",
+        )));
+        assert_eq!(session.execute("fly"), Err(String::from("unknown command \"fly\", try help")));
+        let drawing = render_platforms(&platforms_from(&["ZNCDP", "M", ""])).unwrap();
+        assert_eq!(session.execute("show"), Ok(Reply::Print(drawing)));
+    }
+
+    #[test]
+    fn it_saves_and_loads() {
+        let path = env::temp_dir().join(format!("day5-repl-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut session = Session::new(platforms_from(&["AB", "C"]), &CrateMover9000);
+        session.execute("move 2 from 1 to 2").unwrap();
+        session.execute(&format!("save {}", path)).unwrap();
+
+        let mut other = Session::new(Vec::new(), &CrateMover9000);
+        assert_eq!(other.execute(&format!("load {}", path)), Ok(Reply::Print(format!("loaded {}\n", path))));
+        assert_eq!(other.platforms(), platforms_from(&["", "CBA"]));
+        assert_eq!(other.execute("undo"), Err(String::from("nothing to undo")));
+        fs::remove_file(path).unwrap();
+        assert!(other.execute(&format!("load {}", path)).is_err());
+    }
+
+    #[test]
+    fn it_runs_a_session() {
+        let input = Cursor::new("move 1 from 1 to 2\ntop\nbogus\nquit\ntop\n");
+        let mut output = Vec::new();
+        Session::new(platforms_from(&["AB", "C"]), &CrateMover9000).run(input, &mut output).unwrap();
+        let expect = "> > AB\n> error: unknown command \"bogus\", try help\n> ";
+        assert_eq!(String::from_utf8(output).unwrap(), expect);
+    }
+}
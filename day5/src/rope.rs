@@ -0,0 +1,284 @@
+use crate::crane::{Crane, Lift};
+use crate::error::CargoError;
+use crate::rng::Rng;
+use crate::{check_move_with, Move};
+
+const NIL: usize = usize::MAX;
+
+/// How the stacks are kept while a procedure runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    /// One `Vec` per stack; every crate of a lift is moved by hand.
+    Vec,
+    /// A [`RopePlatforms`], which pays off once lifts carry many crates.
+    Rope,
+}
+
+impl Storage {
+    pub fn from_name(name: &str) -> Option<Storage> {
+        match name {
+            "vec" => Some(Storage::Vec),
+            "rope" => Some(Storage::Rope),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    label: usize,
+    priority: u64,
+    left: usize,
+    right: usize,
+    size: usize,
+    reversed: bool,
+}
+
+/// Platforms stored as one implicit treap per stack, ordered bottom to top.
+/// Taking the top `n` crates off a stack and putting them onto another is a
+/// split and a merge, and a one-at-a-time move only flips a lazy reversal
+/// flag, so every lift costs O(log n) no matter how many crates it carries.
+pub struct RopePlatforms {
+    labels: Vec<String>,
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+impl RopePlatforms {
+    pub fn new(platforms: &[Vec<String>]) -> Self {
+        let mut rope = RopePlatforms {
+            labels: Vec::new(),
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        let mut rng = Rng::new(0x5eed);
+        for platform in platforms {
+            let mut root = NIL;
+            for label in platform {
+                rope.labels.push(label.clone());
+                rope.nodes.push(Node {
+                    label: rope.labels.len() - 1,
+                    priority: rng.next_u64(),
+                    left: NIL,
+                    right: NIL,
+                    size: 1,
+                    reversed: false,
+                });
+                root = rope.merge(root, rope.nodes.len() - 1);
+            }
+            rope.roots.push(root);
+        }
+        rope
+    }
+
+    pub fn stacks(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn height(&self, stack: usize) -> usize {
+        self.size(self.roots[stack])
+    }
+
+    pub fn execute_instructions(&mut self, instructions: &[Move], crane: &dyn Crane) -> Result<(), CargoError> {
+        for instruction in instructions {
+            self.execute_move(instruction, crane)?;
+        }
+        Ok(())
+    }
+
+    pub fn execute_move(&mut self, instruction: &Move, crane: &dyn Crane) -> Result<(), CargoError> {
+        check_move_with(instruction, self.stacks(), |i| self.height(i))?;
+        for lift in crane.lifts(instruction.amount) {
+            self.apply_lift(instruction.source-1, instruction.target-1, lift);
+        }
+        Ok(())
+    }
+
+    /// Same as [`crate::crane::apply_lift`] on the treaps.
+    pub fn apply_lift(&mut self, source: usize, target: usize, lift: Lift) {
+        if source == target {
+            return;
+        }
+        let keep = self.height(source) - lift.count();
+        let (rest, cargo) = self.split(self.roots[source], keep);
+        if let Lift::OneAtATime(_) = lift {
+            if cargo != NIL {
+                self.nodes[cargo].reversed ^= true;
+            }
+        }
+        self.roots[source] = rest;
+        self.roots[target] = self.merge(self.roots[target], cargo);
+    }
+
+    /// Same as [`crate::top_crates`] on the treaps.
+    pub fn top_crates(&self) -> Vec<Option<String>> {
+        self.roots.iter()
+            .map(
+                |root| {
+                    if *root == NIL {
+                        return None;
+                    }
+                    let mut node = *root;
+                    let mut reversed = false;
+                    loop {
+                        reversed ^= self.nodes[node].reversed;
+                        let next = if reversed { self.nodes[node].left } else { self.nodes[node].right };
+                        if next == NIL {
+                            return Some(self.labels[self.nodes[node].label].clone());
+                        }
+                        node = next;
+                    }
+                }
+            )
+            .collect()
+    }
+
+    /// Copies the stacks back into the usual bottom-to-top vectors.
+    pub fn to_platforms(&self) -> Vec<Vec<String>> {
+        self.roots.iter()
+            .map(
+                |root| {
+                    let mut platform = Vec::new();
+                    let mut pending = Vec::from([(*root, false, false)]);
+                    while let Some((node, reversed, visited)) = pending.pop() {
+                        if node == NIL {
+                            continue;
+                        }
+                        if visited {
+                            platform.push(self.labels[self.nodes[node].label].clone());
+                            continue;
+                        }
+                        let reversed = reversed ^ self.nodes[node].reversed;
+                        let (first, last) = if reversed {
+                            (self.nodes[node].right, self.nodes[node].left)
+                        } else {
+                            (self.nodes[node].left, self.nodes[node].right)
+                        };
+                        pending.push((last, reversed, false));
+                        pending.push((node, reversed, true));
+                        pending.push((first, reversed, false));
+                    }
+                    platform
+                }
+            )
+            .collect()
+    }
+
+    fn size(&self, node: usize) -> usize {
+        if node == NIL { 0 } else { self.nodes[node].size }
+    }
+
+    fn update(&mut self, node: usize) {
+        self.nodes[node].size = 1 + self.size(self.nodes[node].left) + self.size(self.nodes[node].right);
+    }
+
+    /// Applies a pending reversal of `node` to its children.
+    fn push_down(&mut self, node: usize) {
+        if !self.nodes[node].reversed {
+            return;
+        }
+        let Node { left, right, .. } = self.nodes[node];
+        self.nodes[node].left = right;
+        self.nodes[node].right = left;
+        self.nodes[node].reversed = false;
+        for child in [left, right] {
+            if child != NIL {
+                self.nodes[child].reversed ^= true;
+            }
+        }
+    }
+
+    /// Splits off the lowest `count` crates; returns `(lowest, rest)`.
+    fn split(&mut self, node: usize, count: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+        self.push_down(node);
+        let left = self.nodes[node].left;
+        if self.size(left) >= count {
+            let (lowest, rest) = self.split(left, count);
+            self.nodes[node].left = rest;
+            self.update(node);
+            (lowest, node)
+        } else {
+            let right = self.nodes[node].right;
+            let (lowest, rest) = self.split(right, count - self.size(left) - 1);
+            self.nodes[node].right = lowest;
+            self.update(node);
+            (node, rest)
+        }
+    }
+
+    /// Puts `upper` on top of `lower`.
+    fn merge(&mut self, lower: usize, upper: usize) -> usize {
+        if lower == NIL {
+            return upper;
+        }
+        if upper == NIL {
+            return lower;
+        }
+        if self.nodes[lower].priority > self.nodes[upper].priority {
+            self.push_down(lower);
+            let right = self.merge(self.nodes[lower].right, upper);
+            self.nodes[lower].right = right;
+            self.update(lower);
+            lower
+        } else {
+            self.push_down(upper);
+            let left = self.merge(lower, self.nodes[upper].left);
+            self.nodes[upper].left = left;
+            self.update(upper);
+            upper
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{BatchCrane, CrateMover9000, CrateMover9001};
+    use crate::execute_instructions;
+    use crate::tests::{platforms_from, tops};
+
+    #[test]
+    fn it_round_trips() {
+        let platforms = platforms_from(&["ZN", "", "MCDQRSTUVW", "P"]);
+        let rope = RopePlatforms::new(&platforms);
+        assert_eq!(rope.to_platforms(), platforms);
+        assert_eq!(rope.top_crates(), tops(&["N", "", "W", "P"]));
+        assert_eq!(rope.height(2), 10);
+    }
+
+    #[test]
+    fn it_matches_vec_execution() {
+        let mut rng = Rng::new(42);
+        let cranes: [&dyn Crane; 3] = [&CrateMover9000, &CrateMover9001, &BatchCrane { capacity: 3 }];
+        for crane in cranes {
+            let mut platforms = platforms_from(&["ABCDEFG", "HIJ", "", "KLMNOPQRSTUVWXYZ"]);
+            let mut rope = RopePlatforms::new(&platforms);
+            let mut instructions = Vec::new();
+            for line in 0..300 {
+                let source = rng.below(4);
+                let target = rng.below(4);
+                let amount = rng.below(platforms[source].len() + 1);
+                let instruction = Move { line, amount, source: source + 1, target: target + 1 };
+                execute_instructions(Vec::from([instruction]), &mut platforms, crane).unwrap();
+                instructions.push(instruction);
+            }
+            rope.execute_instructions(&instructions, crane).unwrap();
+            assert_eq!(rope.to_platforms(), platforms);
+            assert_eq!(rope.top_crates(), crate::top_crates(&platforms));
+        }
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        let mut rope = RopePlatforms::new(&platforms_from(&["AB", "C"]));
+        let instruction = Move { line: 3, amount: 3, source: 1, target: 2 };
+        let expect = CargoError::EmptyStack { line: 3, stack: 1, wanted: 3, available: 2 };
+        assert_eq!(rope.execute_move(&instruction, &CrateMover9000), Err(expect));
+        let instruction = Move { line: 4, amount: 1, source: 1, target: 3 };
+        let expect = CargoError::BadStackIndex { line: 4, stack: 3, stacks: 2 };
+        assert_eq!(rope.execute_move(&instruction, &CrateMover9000), Err(expect));
+    }
+}
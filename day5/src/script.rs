@@ -0,0 +1,394 @@
+//! A small crane instruction language on top of `move N from A to B`:
+//!
+//! ```text
+//! move 3 from 1 to 2             # the usual move
+//! move all from 2 to 3           # everything on stack 2
+//! move 1 from 3 to 1 if top is Z # only when the top crate of 3 is [Z]
+//! swap 1 3                       # exchange two whole stacks
+//! reverse 2                      # turn a stack upside down
+//! rotate 1 by 2                  # top 2 crates go to the bottom, -2 the other way
+//! ```
+//!
+//! Stacks are numbered from 1, `#` starts a comment and blank lines are
+//! ignored. Moves go through the crane model; the other operations work on
+//! the platforms directly.
+
+use std::fmt;
+use std::slice::Iter;
+
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::{check_move, execute_move, Move};
+
+/// How many crates a move takes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Amount {
+    Count(usize),
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Move { amount: Amount, source: usize, target: usize, if_top: Option<String> },
+    Swap { first: usize, second: usize },
+    Reverse { stack: usize },
+    Rotate { stack: usize, by: i64 },
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::Move { amount, source, target, if_top } => {
+                match amount {
+                    Amount::Count(count) => write!(f, "move {} from {} to {}", count, source, target)?,
+                    Amount::All => write!(f, "move all from {} to {}", source, target)?,
+                }
+                match if_top {
+                    Some(label) => write!(f, " if top is [{}]", label),
+                    None => Ok(()),
+                }
+            },
+            Op::Swap { first, second } => write!(f, "swap {} {}", first, second),
+            Op::Reverse { stack } => write!(f, "reverse {}", stack),
+            Op::Rotate { stack, by } => write!(f, "rotate {} by {}", stack, by),
+        }
+    }
+}
+
+/// An operation and the line it was written on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub line: usize,
+    pub op: Op,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    text: String,
+    column: usize,
+}
+
+/// Splits a line into whitespace separated tokens with 1-based columns,
+/// dropping everything from `#` on.
+fn tokenize(l: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    for (i, c) in l.chars().enumerate() {
+        if c == '#' {
+            break;
+        }
+        if c.is_whitespace() {
+            tokens.extend(current.take());
+        } else {
+            current.get_or_insert(Token { text: String::new(), column: i + 1 }).text.push(c);
+        }
+    }
+    tokens.extend(current);
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    line: usize,
+    end_column: usize,
+}
+
+impl Parser {
+    fn error(&self, expected: &str) -> CargoError {
+        let (column, found) = match self.tokens.get(self.position) {
+            Some(token) => (token.column, format!("{:?}", token.text)),
+            None => (self.end_column, String::from("end of line")),
+        };
+        CargoError::Syntax {
+            line: self.line,
+            column,
+            message: format!("expected {}, found {}", expected, found),
+        }
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.text.as_str())
+    }
+
+    fn next(&mut self, expected: &str) -> Result<String, CargoError> {
+        let token = self.tokens.get(self.position).ok_or_else(|| self.error(expected))?;
+        self.position += 1;
+        Ok(token.text.clone())
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), CargoError> {
+        if self.peek() != Some(keyword) {
+            return Err(self.error(&format!("`{}`", keyword)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, expected: &str) -> Result<T, CargoError> {
+        let is_number = self.peek().is_some_and(
+            |text| {
+                let digits = text.strip_prefix('-').unwrap_or(text);
+                !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+            }
+        );
+        if !is_number {
+            return Err(self.error(expected));
+        }
+        let text = self.next(expected)?;
+        text.parse().map_err(|_| CargoError::BadNumber { line: self.line, value: text })
+    }
+
+    fn label(&mut self) -> Result<String, CargoError> {
+        let text = self.next("crate label")?;
+        match text.strip_prefix('[').and_then(|text| text.strip_suffix(']')) {
+            Some(label) if !label.is_empty() => Ok(label.to_string()),
+            Some(_) => {
+                self.position -= 1;
+                Err(self.error("crate label"))
+            },
+            None => Ok(text),
+        }
+    }
+
+    fn end(&self) -> Result<(), CargoError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("end of line")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Op, CargoError> {
+        let op = match self.peek() {
+            Some("move") => {
+                self.position += 1;
+                let amount = if self.peek() == Some("all") {
+                    self.position += 1;
+                    Amount::All
+                } else {
+                    Amount::Count(self.number("crate count or `all`")?)
+                };
+                self.keyword("from")?;
+                let source = self.number("stack number")?;
+                self.keyword("to")?;
+                let target = self.number("stack number")?;
+                let if_top = if self.peek() == Some("if") {
+                    self.position += 1;
+                    self.keyword("top")?;
+                    self.keyword("is")?;
+                    Some(self.label()?)
+                } else {
+                    None
+                };
+                Op::Move { amount, source, target, if_top }
+            },
+            Some("swap") => {
+                self.position += 1;
+                let first = self.number("stack number")?;
+                let second = self.number("stack number")?;
+                Op::Swap { first, second }
+            },
+            Some("reverse") => {
+                self.position += 1;
+                Op::Reverse { stack: self.number("stack number")? }
+            },
+            Some("rotate") => {
+                self.position += 1;
+                let stack = self.number("stack number")?;
+                self.keyword("by")?;
+                let by = self.number("rotation")?;
+                Op::Rotate { stack, by }
+            },
+            _ => return Err(self.error("`move`, `swap`, `reverse` or `rotate`")),
+        };
+        self.end()?;
+        Ok(op)
+    }
+}
+
+/// Parses one script line, or returns `None` for blank and comment lines.
+pub fn parse_line(l: &str, line: usize) -> Option<Result<Statement, CargoError>> {
+    let tokens = tokenize(l);
+    if tokens.is_empty() {
+        return None;
+    }
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        line,
+        end_column: l.split('#').next().unwrap_or("").trim_end().chars().count() + 1,
+    };
+    Some(parser.statement().map(|op| Statement { line, op }))
+}
+
+/// Parses a script, where `first_line` is the line number of the first entry
+/// in `lines`. Unlike the plain procedure parser, unknown lines are errors.
+pub fn parse_script(lines: Iter<&str>, first_line: usize) -> Result<Vec<Statement>, CargoError> {
+    lines.enumerate()
+        .flat_map(|(i, l)| parse_line(l, first_line + i))
+        .collect()
+}
+
+pub fn execute_script(statements: &[Statement], platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    for statement in statements {
+        execute_statement(statement, platforms, crane)?;
+    }
+    Ok(())
+}
+
+pub fn execute_statement(statement: &Statement, platforms: &mut [Vec<String>], crane: &dyn Crane) -> Result<(), CargoError> {
+    let line = statement.line;
+    let stack = |stack: usize| -> Result<usize, CargoError> {
+        if stack == 0 || stack > platforms.len() {
+            return Err(CargoError::BadStackIndex { line, stack, stacks: platforms.len() });
+        }
+        Ok(stack - 1)
+    };
+    match &statement.op {
+        Op::Move { amount, source, target, if_top } => {
+            let mut instruction = Move { line, amount: 0, source: *source, target: *target };
+            check_move(&instruction, platforms)?;
+            instruction.amount = match amount {
+                Amount::Count(count) => *count,
+                Amount::All => platforms[source-1].len(),
+            };
+            if let Some(label) = if_top {
+                if platforms[source-1].last() != Some(label) {
+                    return Ok(());
+                }
+            }
+            execute_move(&instruction, platforms, crane)?;
+        },
+        Op::Swap { first, second } => {
+            let (first, second) = (stack(*first)?, stack(*second)?);
+            platforms.swap(first, second);
+        },
+        Op::Reverse { stack: index } => {
+            let index = stack(*index)?;
+            platforms[index].reverse();
+        },
+        Op::Rotate { stack: index, by } => {
+            let index = stack(*index)?;
+            let platform = &mut platforms[index];
+            if !platform.is_empty() {
+                let shift = by.unsigned_abs() as usize % platform.len();
+                if *by >= 0 {
+                    platform.rotate_right(shift);
+                } else {
+                    platform.rotate_left(shift);
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Runs a puzzle input whose procedure is written in the script language
+/// and returns the final platforms.
+pub fn run_script(input: &str, crane: &dyn Crane) -> Result<Vec<Vec<String>>, CargoError> {
+    let lines = crate::split_lines(input);
+    let instruction_idx = crate::find_instruction_line(&lines)?;
+    let mut platforms = crate::parse_drawing(input)?;
+    let statements = parse_script(lines[(instruction_idx+1)..].iter(), instruction_idx + 2)?;
+    execute_script(&statements, &mut platforms, crane)?;
+    Ok(platforms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::platforms_from;
+
+    fn parse(l: &str) -> Result<Op, CargoError> {
+        parse_line(l, 1).unwrap().map(|statement| statement.op)
+    }
+
+    #[test]
+    fn it_parses_every_op() {
+        let cases = [
+            ("move 3 from 1 to 2", Op::Move { amount: Amount::Count(3), source: 1, target: 2, if_top: None }),
+            ("  move all from 2 to 3  # empty 2", Op::Move { amount: Amount::All, source: 2, target: 3, if_top: None }),
+            ("move 1 from 3 to 1 if top is Z", Op::Move {
+                amount: Amount::Count(1), source: 3, target: 1, if_top: Some(String::from("Z")),
+            }),
+            ("move all from 3 to 1 if top is [AB]", Op::Move {
+                amount: Amount::All, source: 3, target: 1, if_top: Some(String::from("AB")),
+            }),
+            ("swap 1 3", Op::Swap { first: 1, second: 3 }),
+            ("reverse 2", Op::Reverse { stack: 2 }),
+            ("rotate 1 by -2", Op::Rotate { stack: 1, by: -2 }),
+        ];
+        for (text, op) in cases {
+            assert_eq!(parse(text), Ok(op.clone()));
+            assert_eq!(parse(&op.to_string()), Ok(op));
+        }
+        assert_eq!(parse_line("   # just a comment", 1), None);
+        assert_eq!(parse_line("", 1), None);
+    }
+
+    #[test]
+    fn it_reports_syntax_errors() {
+        let error = |column: usize, message: &str| Err(CargoError::Syntax {
+            line: 1,
+            column,
+            message: String::from(message),
+        });
+        assert_eq!(parse("mvoe 1 from 2 to 3"), error(1, "expected `move`, `swap`, `reverse` or `rotate`, found \"mvoe\""));
+        assert_eq!(parse("move some from 2 to 3"), error(6, "expected crate count or `all`, found \"some\""));
+        assert_eq!(parse("move 1 from 2 onto 3"), error(15, "expected `to`, found \"onto\""));
+        assert_eq!(parse("swap 1 # no second"), error(7, "expected stack number, found end of line"));
+        assert_eq!(parse("reverse 1 2"), error(11, "expected end of line, found \"2\""));
+        assert_eq!(parse("move 1 from 2 to 3 if top is []"), error(30, "expected crate label, found \"[]\""));
+        assert_eq!(parse("rotate 1 by 99999999999999999999"), Err(CargoError::BadNumber {
+            line: 1,
+            value: String::from("99999999999999999999"),
+        }));
+    }
+
+    #[test]
+    fn it_executes_a_script() {
+        let script = [
+            "move 1 from 2 to 1   # like the example",
+            "move all from 1 to 3",
+            "move 1 from 2 to 1 if top is X",
+            "move 1 from 2 to 1 if top is C",
+            "",
+            "swap 1 2",
+            "reverse 3",
+            "rotate 3 by 1",
+        ];
+        let statements = parse_script(script.iter(), 10).unwrap();
+        assert_eq!(statements.len(), 7);
+        assert_eq!(statements[6].line, 17);
+
+        let mut platforms = platforms_from(&["ZN", "MCD", "P"]);
+        execute_script(&statements, &mut platforms, &CrateMover9000).unwrap();
+        assert_eq!(platforms, platforms_from(&["M", "C", "PZND"]));
+    }
+
+    #[test]
+    fn it_checks_stacks() {
+        let mut platforms = platforms_from(&["A", ""]);
+        let statements = parse_script(["swap 1 3"].iter(), 4).unwrap();
+        let actual = execute_script(&statements, &mut platforms, &CrateMover9001);
+        assert_eq!(actual, Err(CargoError::BadStackIndex { line: 4, stack: 3, stacks: 2 }));
+
+        let statements = parse_script(["move all from 2 to 1 if top is A", "move 2 from 1 to 2"].iter(), 1).unwrap();
+        let actual = execute_script(&statements, &mut platforms, &CrateMover9001);
+        assert_eq!(actual, Err(CargoError::EmptyStack { line: 2, stack: 1, wanted: 2, available: 1 }));
+    }
+
+    #[test]
+    fn it_runs_a_puzzle_input() {
+        let input = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove all from 2 to 1\nreverse 1\n";
+        let platforms = run_script(input, &CrateMover9001).unwrap();
+        assert_eq!(platforms, platforms_from(&["DCMNZ", "", "P"]));
+        let platforms = run_script(&input.replace('\n', "\r\n"), &CrateMover9001).unwrap();
+        assert_eq!(platforms, platforms_from(&["DCMNZ", "", "P"]));
+
+        let input = "[A]\n 1 \n\nmove 1 from 1 to 1\nfly away\n";
+        let actual = run_script(input, &CrateMover9001);
+        assert!(matches!(actual, Err(CargoError::Syntax { line: 5, column: 1, .. })));
+    }
+}
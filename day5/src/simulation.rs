@@ -0,0 +1,256 @@
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::{execute_move, Move};
+
+/// How many moves apart the simulation keeps copies of the platforms, so that
+/// jumping backwards only replays a handful of moves.
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// The platforms after `step` moves have been applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub step: usize,
+    pub platforms: Vec<Vec<String>>,
+}
+
+/// What happened to one stack between two snapshots. Both sides share the
+/// bottom `common` crates; `removed` and `added` are the crates above them,
+/// bottom to top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackDiff {
+    pub stack: usize,
+    pub common: usize,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+impl Snapshot {
+    /// Lists every stack that differs between `self` and `other`.
+    pub fn diff(&self, other: &Snapshot) -> Vec<StackDiff> {
+        let stacks = self.platforms.len().max(other.platforms.len());
+        (0..stacks).filter_map(
+            |i| {
+                let before = self.platforms.get(i).map(Vec::as_slice).unwrap_or(&[]);
+                let after = other.platforms.get(i).map(Vec::as_slice).unwrap_or(&[]);
+                if before == after {
+                    return None;
+                }
+                let common = before.iter()
+                    .zip(after)
+                    .take_while(|(b, a)| b == a)
+                    .count();
+                Some(StackDiff {
+                    stack: i + 1,
+                    common,
+                    removed: before[common..].to_vec(),
+                    added: after[common..].to_vec(),
+                })
+            }
+        ).collect()
+    }
+}
+
+/// A single move applied by the simulation, with the platforms right after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub instruction: Move,
+    pub snapshot: Snapshot,
+}
+
+/// Runs a procedure one move at a time. Iterating yields a [`Step`] per move
+/// and stops after the last move or the first error.
+pub struct Simulation<'a> {
+    crane: &'a dyn Crane,
+    instructions: Vec<Move>,
+    checkpoints: Vec<Vec<Vec<String>>>,
+    platforms: Vec<Vec<String>>,
+    position: usize,
+    halted: bool,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(platforms: Vec<Vec<String>>, instructions: Vec<Move>, crane: &'a dyn Crane) -> Self {
+        Simulation {
+            crane,
+            instructions,
+            checkpoints: Vec::from([platforms.clone()]),
+            platforms,
+            position: 0,
+            halted: false,
+        }
+    }
+
+    /// Number of moves applied so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    pub fn instructions(&self) -> &[Move] {
+        &self.instructions
+    }
+
+    pub fn platforms(&self) -> &[Vec<String>] {
+        &self.platforms
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            step: self.position,
+            platforms: self.platforms.clone(),
+        }
+    }
+
+    /// Goes back to the starting platforms.
+    pub fn rewind(&mut self) {
+        self.platforms = self.checkpoints[0].clone();
+        self.position = 0;
+        self.halted = false;
+    }
+
+    /// Moves to the state after `step` moves, replaying from the closest
+    /// checkpoint when going backwards. Steps past the end stop at the end.
+    pub fn seek(&mut self, step: usize) -> Result<Snapshot, CargoError> {
+        let step = step.min(self.instructions.len());
+        if step < self.position {
+            let checkpoint = (step / CHECKPOINT_INTERVAL).min(self.checkpoints.len() - 1);
+            self.platforms = self.checkpoints[checkpoint].clone();
+            self.position = checkpoint * CHECKPOINT_INTERVAL;
+        }
+        self.halted = false;
+        while self.position < step {
+            self.advance()?;
+        }
+        Ok(self.snapshot())
+    }
+
+    fn advance(&mut self) -> Result<Step, CargoError> {
+        let instruction = self.instructions[self.position];
+        if let Err(error) = execute_move(&instruction, &mut self.platforms, self.crane) {
+            self.halted = true;
+            return Err(error);
+        }
+        self.position += 1;
+        if self.position.is_multiple_of(CHECKPOINT_INTERVAL)
+            && self.checkpoints.len() == self.position / CHECKPOINT_INTERVAL {
+            self.checkpoints.push(self.platforms.clone());
+        }
+        Ok(Step {
+            instruction,
+            snapshot: self.snapshot(),
+        })
+    }
+}
+
+impl Iterator for Simulation<'_> {
+    type Item = Result<Step, CargoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.halted || self.position >= self.instructions.len() {
+            return None;
+        }
+        Some(self.advance())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::{labels, platforms_from};
+
+    fn example() -> (Vec<Vec<String>>, Vec<Move>) {
+        let platforms = platforms_from(&["ZN", "MCD", "P"]);
+        let instructions = Vec::from([
+            Move { line: 6, amount: 1, source: 2, target: 1 },
+            Move { line: 7, amount: 3, source: 1, target: 3 },
+            Move { line: 8, amount: 2, source: 2, target: 1 },
+            Move { line: 9, amount: 1, source: 1, target: 2 },
+        ]);
+        (platforms, instructions)
+    }
+
+    #[test]
+    fn it_iterates_steps() {
+        let (platforms, instructions) = example();
+        let simulation = Simulation::new(platforms, instructions.clone(), &CrateMover9000);
+        let steps: Vec<_> = simulation.collect::<Result<_, _>>().unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0].instruction, instructions[0]);
+        assert_eq!(steps[0].snapshot.step, 1);
+        let expect = platforms_from(&["ZND", "MC", "P"]);
+        assert_eq!(steps[0].snapshot.platforms, expect);
+        let expect = platforms_from(&["C", "M", "PDNZ"]);
+        assert_eq!(steps[3].snapshot.platforms, expect);
+    }
+
+    #[test]
+    fn it_seeks_and_rewinds() {
+        let (platforms, instructions) = example();
+        let mut simulation = Simulation::new(platforms.clone(), instructions, &CrateMover9001);
+        let end = simulation.seek(10).unwrap();
+        assert_eq!(end.step, 4);
+        assert!(simulation.next().is_none());
+
+        let second = simulation.seek(2).unwrap();
+        let expect = platforms_from(&["", "MC", "PZND"]);
+        assert_eq!(second.platforms, expect);
+        assert_eq!(simulation.next().unwrap().unwrap().snapshot.step, 3);
+
+        simulation.rewind();
+        assert_eq!(simulation.position(), 0);
+        assert_eq!(simulation.platforms(), platforms.as_slice());
+    }
+
+    #[test]
+    fn it_seeks_across_checkpoints() {
+        let platforms = platforms_from(&["AB", ""]);
+        let instructions: Vec<_> = (0..200)
+            .map(|i| Move { line: i + 1, amount: 1, source: i % 2 + 1, target: 2 - i % 2 })
+            .collect();
+        let mut simulation = Simulation::new(platforms, instructions, &CrateMover9000);
+        simulation.seek(200).unwrap();
+        let back = simulation.seek(131).unwrap();
+        let mut fresh = Simulation::new(
+            simulation.checkpoints[0].clone(),
+            simulation.instructions().to_vec(),
+            &CrateMover9000,
+        );
+        assert_eq!(back, fresh.seek(131).unwrap());
+    }
+
+    #[test]
+    fn it_stops_on_error() {
+        let (platforms, mut instructions) = example();
+        instructions[1].amount = 5;
+        let mut simulation = Simulation::new(platforms, instructions, &CrateMover9000);
+        assert!(simulation.next().unwrap().is_ok());
+        let expect = CargoError::EmptyStack { line: 7, stack: 1, wanted: 5, available: 3 };
+        assert_eq!(simulation.next(), Some(Err(expect)));
+        assert_eq!(simulation.next(), None);
+        assert_eq!(simulation.position(), 1);
+    }
+
+    #[test]
+    fn it_diffs_snapshots() {
+        let (platforms, instructions) = example();
+        let mut simulation = Simulation::new(platforms, instructions, &CrateMover9000);
+        let start = simulation.snapshot();
+        let second = simulation.seek(2).unwrap();
+        let actual = start.diff(&second);
+        let expect = Vec::from([
+            StackDiff { stack: 1, common: 0, removed: labels("ZN"), added: Vec::new() },
+            StackDiff { stack: 2, common: 2, removed: labels("D"), added: Vec::new() },
+            StackDiff { stack: 3, common: 1, removed: Vec::new(), added: labels("DNZ") },
+        ]);
+        assert_eq!(actual, expect);
+        assert!(second.diff(&second).is_empty());
+    }
+}
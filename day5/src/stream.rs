@@ -0,0 +1,193 @@
+use std::io::BufRead;
+
+use crate::crane::Crane;
+use crate::error::CargoError;
+use crate::rope::{RopePlatforms, Storage};
+use crate::{build_platform_idxs, execute_move, load_platforms, parse_move_line, top_crates};
+
+/// Runs a puzzle input straight from a reader. Only the drawing is kept in
+/// memory; the procedure is read into one reused line buffer and every
+/// instruction is applied as soon as it is parsed, so the size of the crane
+/// log does not matter.
+pub struct Stream<R> {
+    reader: R,
+    buffer: String,
+    line: usize,
+    stacks: Stacks,
+    instructions: usize,
+}
+
+/// The stacks of a [`Stream`] in the chosen [`Storage`].
+enum Stacks {
+    Vec(Vec<Vec<String>>),
+    Rope(RopePlatforms),
+}
+
+impl<R: BufRead> Stream<R> {
+    /// Reads the drawing up to and including the blank line after it.
+    pub fn new(reader: R) -> Result<Self, CargoError> {
+        Stream::with_storage(reader, Storage::Vec)
+    }
+
+    /// Same as [`Stream::new`], keeping the stacks in `storage`.
+    pub fn with_storage(reader: R, storage: Storage) -> Result<Self, CargoError> {
+        let mut stream = Stream {
+            reader,
+            buffer: String::new(),
+            line: 0,
+            stacks: Stacks::Vec(Vec::new()),
+            instructions: 0,
+        };
+        let mut drawing = Vec::new();
+        loop {
+            match stream.next_line()? {
+                None => return Err(CargoError::MissingSeparator),
+                Some("") => break,
+                Some(l) => drawing.push(l.to_string()),
+            }
+        }
+        let Some((footer, rows)) = drawing.split_last() else {
+            return Err(CargoError::MissingFooter);
+        };
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        let platform_idxs = build_platform_idxs(footer, drawing.len())?;
+        let platforms = load_platforms(rows.iter().rev().collect(), platform_idxs)?;
+        stream.stacks = match storage {
+            Storage::Vec => Stacks::Vec(platforms),
+            Storage::Rope => Stacks::Rope(RopePlatforms::new(&platforms)),
+        };
+        Ok(stream)
+    }
+
+    /// Reads the next line into the buffer without its line ending, or
+    /// returns `None` at the end of the input.
+    fn next_line(&mut self) -> Result<Option<&str>, CargoError> {
+        self.buffer.clear();
+        let read = self.reader.read_line(&mut self.buffer)
+            .map_err(|e| CargoError::Read { line: self.line, message: e.to_string() })?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.line += 1;
+        let l = self.buffer.strip_suffix('\n').unwrap_or(&self.buffer);
+        Ok(Some(l.strip_suffix('\r').unwrap_or(l)))
+    }
+
+    /// Applies the next instruction, skipping lines that are not `move`
+    /// instructions like [`crate::parse_move_instructions`] does. Returns
+    /// `false` once the input is used up.
+    pub fn step(&mut self, crane: &dyn Crane) -> Result<bool, CargoError> {
+        loop {
+            let line = self.line + 1;
+            let parsed = match self.next_line()? {
+                None => return Ok(false),
+                Some(l) => parse_move_line(l, line),
+            };
+            if let Some(instruction) = parsed {
+                let instruction = instruction?;
+                match &mut self.stacks {
+                    Stacks::Vec(platforms) => execute_move(&instruction, platforms, crane)?,
+                    Stacks::Rope(rope) => rope.execute_move(&instruction, crane)?,
+                }
+                self.instructions += 1;
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Applies every remaining instruction.
+    pub fn run(&mut self, crane: &dyn Crane) -> Result<(), CargoError> {
+        while self.step(crane)? {}
+        Ok(())
+    }
+
+    /// A copy of the stacks as they are now.
+    pub fn platforms(&self) -> Vec<Vec<String>> {
+        match &self.stacks {
+            Stacks::Vec(platforms) => platforms.clone(),
+            Stacks::Rope(rope) => rope.to_platforms(),
+        }
+    }
+
+    /// Same as [`crate::top_crates`] for the stacks as they are now.
+    pub fn top_crates(&self) -> Vec<Option<String>> {
+        match &self.stacks {
+            Stacks::Vec(platforms) => top_crates(platforms),
+            Stacks::Rope(rope) => rope.top_crates(),
+        }
+    }
+
+    /// How many instructions have been applied so far.
+    pub fn instructions(&self) -> usize {
+        self.instructions
+    }
+
+    /// How many lines have been read so far.
+    pub fn lines(&self) -> usize {
+        self.line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, Cursor, Read};
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::parse_cargo;
+    use crate::tests::{tops, TEST_INPUT};
+
+    #[test]
+    fn it_matches_parse_cargo() {
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let mut stream = Stream::new(Cursor::new(TEST_INPUT)).unwrap();
+            stream.run(crane).unwrap();
+            assert_eq!(stream.top_crates(), parse_cargo(TEST_INPUT, crane).unwrap());
+            assert_eq!(stream.instructions(), 4);
+            assert_eq!(stream.lines(), 9);
+
+            let mut rope = Stream::with_storage(Cursor::new(TEST_INPUT), Storage::Rope).unwrap();
+            rope.run(crane).unwrap();
+            assert_eq!(rope.platforms(), stream.platforms());
+            assert_eq!(rope.top_crates(), stream.top_crates());
+        }
+
+        let crlf = TEST_INPUT.replace('\n', "\r\n");
+        let mut stream = Stream::new(Cursor::new(crlf)).unwrap();
+        stream.run(&CrateMover9000).unwrap();
+        assert_eq!(stream.top_crates(), tops(&["C", "M", "Z"]));
+    }
+
+    #[test]
+    fn it_steps_one_instruction_at_a_time() {
+        let input = "[A]    \n 1   2 \n\nmove 1 from 1 to 2\n\nnoise\nmove 1 from 2 to 1";
+        let mut stream = Stream::new(Cursor::new(input)).unwrap();
+        assert_eq!(stream.platforms(), &[Vec::from([String::from("A")]), Vec::new()]);
+        assert_eq!(stream.step(&CrateMover9000), Ok(true));
+        assert_eq!(stream.platforms(), &[Vec::new(), Vec::from([String::from("A")])]);
+        assert_eq!(stream.step(&CrateMover9000), Ok(true));
+        assert_eq!(stream.lines(), 7);
+        assert_eq!(stream.step(&CrateMover9000), Ok(false));
+        assert_eq!(stream.instructions(), 2);
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        assert!(matches!(Stream::new(Cursor::new("[A]\n 1 \n")), Err(CargoError::MissingSeparator)));
+        assert!(matches!(Stream::new(Cursor::new("\nmove 1 from 1 to 2")), Err(CargoError::MissingFooter)));
+
+        let mut stream = Stream::new(Cursor::new("[A]\n 1 \n\nmove 1 from 1 to 1\nmove 2 from 1 to 1\n")).unwrap();
+        let expect = CargoError::EmptyStack { line: 5, stack: 1, wanted: 2, available: 1 };
+        assert_eq!(stream.run(&CrateMover9000), Err(expect));
+
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk on fire"))
+            }
+        }
+        let reader = io::BufReader::new(Cursor::new("[A]\n 1 \n\n").chain(Broken));
+        let mut stream = Stream::new(reader).unwrap();
+        let expect = CargoError::Read { line: 3, message: String::from("disk on fire") };
+        assert_eq!(stream.run(&CrateMover9000), Err(expect));
+    }
+}
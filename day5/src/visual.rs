@@ -0,0 +1,339 @@
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::crane::{Crane, Lift};
+use crate::error::CargoError;
+use crate::render::cell_width;
+use crate::simulation::Simulation;
+use crate::Move;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const HELD_COLOR: &str = "\x1b[1;33m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// One picture of the animation: the crates resting on the platforms plus
+/// the crates hanging from the crane, bottom to top. Every column is `width`
+/// characters wide, `x` is where the held crates' column starts and `level`
+/// is the row of the lowest held crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub platforms: Vec<Vec<String>>,
+    pub held: Vec<String>,
+    pub width: usize,
+    pub x: usize,
+    pub level: usize,
+}
+
+/// Builds the frames for one instruction: every lift of the crane rises
+/// above the tallest stack, travels sideways and drops onto the target.
+pub fn animate_move(platforms: &[Vec<String>], instruction: &Move, crane: &dyn Crane) -> Vec<Frame> {
+    let width = cell_width(platforms);
+    let mut platforms = platforms.to_vec();
+    let source = instruction.source - 1;
+    let target = instruction.target - 1;
+    let mut frames = Vec::new();
+    for lift in crane.lifts(instruction.amount) {
+        let (count, batch) = match lift {
+            Lift::OneAtATime(count) => (count, 1),
+            Lift::AllAtOnce(count) => (count, count.max(1)),
+        };
+        let mut moved = 0;
+        while moved < count {
+            let size = batch.min(count - moved);
+            animate_lift(&mut platforms, (source, target), size, width, &mut frames);
+            moved += size;
+        }
+    }
+    frames
+}
+
+fn animate_lift(platforms: &mut [Vec<String>], (source, target): (usize, usize), size: usize, width: usize, frames: &mut Vec<Frame>) {
+    let from = &mut platforms[source];
+    let held = from.split_off(from.len() - size);
+    let ceiling = platforms.iter().map(Vec::len).max().unwrap_or(0);
+    let mut frame = Frame {
+        platforms: platforms.to_vec(),
+        held,
+        width,
+        x: column(source, width),
+        level: platforms[source].len(),
+    };
+    frames.push(frame.clone());
+    while frame.level < ceiling {
+        frame.level += 1;
+        frames.push(frame.clone());
+    }
+    let x = column(target, width);
+    while frame.x != x {
+        if frame.x < x { frame.x += 1 } else { frame.x -= 1 }
+        frames.push(frame.clone());
+    }
+    while frame.level > platforms[target].len() {
+        frame.level -= 1;
+        frames.push(frame.clone());
+    }
+    platforms[target].append(&mut frame.held);
+    frame.platforms = platforms.to_vec();
+    frames.push(frame);
+}
+
+fn column(stack: usize, width: usize) -> usize {
+    stack * (width + 1)
+}
+
+/// Draws a frame `height` rows tall above the stack number footer. Held
+/// crates are highlighted and hang from a rope reaching the top row.
+pub fn render_frame(frame: &Frame, height: usize) -> String {
+    let width = frame.width;
+    let columns = column(frame.platforms.len(), width).max(frame.x + width);
+    let mut grid = vec![vec![(' ', false); columns]; height];
+    let mut put = |level: usize, x: usize, label: &str, held: bool| {
+        if level < height {
+            let cell = format!("{:^width$}", format!("[{}]", label));
+            for (offset, c) in cell.chars().enumerate() {
+                grid[height - 1 - level][x + offset] = (c, held && c != ' ');
+            }
+        }
+    };
+    for (i, platform) in frame.platforms.iter().enumerate() {
+        for (level, label) in platform.iter().enumerate() {
+            put(level, column(i, width), label, false);
+        }
+    }
+    for (offset, label) in frame.held.iter().enumerate() {
+        put(frame.level + offset, frame.x, label, true);
+    }
+    if !frame.held.is_empty() {
+        for level in (frame.level + frame.held.len())..height {
+            grid[height - 1 - level][frame.x + width / 2] = ('|', false);
+        }
+    }
+
+    let mut picture = String::new();
+    for row in grid {
+        let mut line = String::new();
+        let mut highlighted = false;
+        for (c, held) in row {
+            if held != highlighted {
+                line.push_str(if held { HELD_COLOR } else { RESET_COLOR });
+                highlighted = held;
+            }
+            line.push(c);
+        }
+        if highlighted {
+            line.push_str(RESET_COLOR);
+        }
+        picture.push_str(line.trim_end());
+        picture.push('\n');
+    }
+    let footer: Vec<_> = (1..=frame.platforms.len())
+        .map(|i| format!("{:^width$}", i))
+        .collect();
+    picture.push_str(&footer.join(" "));
+    picture.push('\n');
+    picture
+}
+
+/// Keys understood while the animation runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Pause,
+    Faster,
+    Slower,
+    Quit,
+}
+
+fn control_for(key: u8) -> Option<Control> {
+    match key {
+        b' ' | b'p' => Some(Control::Pause),
+        b'+' | b'=' => Some(Control::Faster),
+        b'-' | b'_' => Some(Control::Slower),
+        // Ctrl-C arrives as a key because the raw terminal turns off signals.
+        b'q' | 0x03 => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+/// Puts the terminal into unbuffered, non-echoing mode with the cursor
+/// hidden for as long as it lives, so single key presses reach the animation
+/// without Enter. Signal keys are turned off too: Ctrl-C would kill the
+/// process before the terminal is restored, so it is read as a quit key.
+struct RawTerminal {
+    active: bool,
+}
+
+impl RawTerminal {
+    fn enable() -> Self {
+        let active = Command::new("stty")
+            .args(["-icanon", "-echo", "-isig"])
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        print!("{}", HIDE_CURSOR);
+        let _ = io::stdout().flush();
+        RawTerminal { active }
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("{}", SHOW_CURSOR);
+        let _ = io::stdout().flush();
+        if self.active {
+            let _ = Command::new("stty").args(["icanon", "echo", "isig"]).status();
+        }
+    }
+}
+
+fn spawn_controls() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for key in io::stdin().lock().bytes() {
+            let Some(control) = key.ok().and_then(control_for) else {
+                continue;
+            };
+            if sender.send(control).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Plays the whole procedure in the terminal and returns the top crates,
+/// or `None` if it was stopped early. Space or `p` pauses, `+` and `-`
+/// change the speed and `q` stops early.
+pub fn play(platforms: Vec<Vec<String>>, instructions: Vec<Move>, crane: &dyn Crane, delay: Duration) -> Result<Option<Vec<Option<String>>>, CargoError> {
+    let height = platforms.iter().map(Vec::len).sum::<usize>() + 2;
+    let total = instructions.len();
+    let mut simulation = Simulation::new(platforms, instructions, crane);
+    let _terminal = RawTerminal::enable();
+    let controls = spawn_controls();
+    let mut delay = delay;
+    let mut paused = false;
+    let mut quit = false;
+    let mut stdout = io::stdout();
+
+    'steps: while simulation.position() < total {
+        let before = simulation.platforms().to_vec();
+        let instruction = simulation.instructions()[simulation.position()];
+        simulation.next().unwrap()?;
+        for frame in animate_move(&before, &instruction, crane) {
+            loop {
+                let control = if paused {
+                    controls.recv().map_err(|_| paused = false).ok()
+                } else {
+                    controls.try_recv().ok()
+                };
+                match control {
+                    Some(Control::Pause) => paused = !paused,
+                    Some(Control::Faster) => delay /= 2,
+                    Some(Control::Slower) => delay = (delay * 2).max(Duration::from_millis(1)),
+                    Some(Control::Quit) => {
+                        quit = true;
+                        break 'steps;
+                    },
+                    None => (),
+                }
+                print!("{}{}", CLEAR_SCREEN, render_frame(&frame, height));
+                println!(
+                    "step {}/{}  {}  {}  {}ms{}",
+                    simulation.position(), total, instruction, crane.name(),
+                    delay.as_millis(), if paused { "  [paused]" } else { "" },
+                );
+                println!("space: pause  +/-: speed  q: quit");
+                let _ = stdout.flush();
+                if !paused {
+                    break;
+                }
+            }
+            thread::sleep(delay);
+        }
+    }
+    if quit {
+        return Ok(None);
+    }
+    Ok(Some(crate::top_crates(simulation.platforms())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::{labels, platforms_from};
+
+    fn example() -> Vec<Vec<String>> {
+        platforms_from(&["ZN", "MCD", "P"])
+    }
+
+    #[test]
+    fn it_animate_move() {
+        let instruction = Move { line: 7, amount: 2, source: 2, target: 1 };
+        let frames = animate_move(&example(), &instruction, &CrateMover9000);
+        let expect = platforms_from(&["ZNDC", "M", "P"]);
+        let last = frames.last().unwrap();
+        assert_eq!(last.platforms, expect);
+        assert!(last.held.is_empty());
+        assert!(frames.iter().all(|frame| frame.held.len() <= 1));
+
+        let frames = animate_move(&example(), &instruction, &CrateMover9001);
+        assert_eq!(frames[0].held, labels("CD"));
+        assert_eq!(frames.last().unwrap().platforms[0], labels("ZNCD"));
+    }
+
+    #[test]
+    fn it_animate_move_path() {
+        let instruction = Move { line: 7, amount: 1, source: 3, target: 1 };
+        let frames = animate_move(&example(), &instruction, &CrateMover9000);
+        let path: Vec<_> = frames.iter().map(|frame| (frame.x, frame.level)).collect();
+        let mut expect = Vec::from([(8, 0), (8, 1), (8, 2), (8, 3)]);
+        expect.extend((0..8).rev().map(|x| (x, 3)));
+        expect.extend([(0, 2), (0, 2)]);
+        assert_eq!(path, expect);
+    }
+
+    #[test]
+    fn it_maps_keys_to_controls() {
+        assert_eq!(control_for(b' '), Some(Control::Pause));
+        assert_eq!(control_for(b'q'), Some(Control::Quit));
+        assert_eq!(control_for(0x03), Some(Control::Quit));
+        assert_eq!(control_for(b'x'), None);
+    }
+
+    #[test]
+    fn it_render_frame() {
+        let frame = Frame {
+            platforms: platforms_from(&["Z", "", "P"]),
+            held: labels("N"),
+            width: 3,
+            x: 4,
+            level: 2,
+        };
+        let actual = render_frame(&frame, 4);
+        let expect = format!(
+            "     |\n    {}[N]{}\n\n[Z]     [P]\n 1   2   3 \n",
+            HELD_COLOR, RESET_COLOR,
+        );
+        assert_eq!(actual, expect);
+
+        let frame = Frame {
+            platforms: Vec::from([labels("A"), Vec::new()]),
+            held: Vec::from([String::from("BC")]),
+            width: 4,
+            x: 2,
+            level: 1,
+        };
+        let actual = render_frame(&frame, 3);
+        let expect = format!(
+            "    |\n  {}[BC]{}\n[A]\n 1    2  \n",
+            HELD_COLOR, RESET_COLOR,
+        );
+        assert_eq!(actual, expect);
+    }
+}
@@ -0,0 +1,68 @@
+use std::env;
+use std::process;
+use std::time::Instant;
+use day6::{find_starter_packet, find_starter_packet_naive};
+
+const USAGE: &str = "usage: bench [--megabytes N] [--window N] [--alphabet N] [--seed N]";
+
+/// Times the sliding window against the original scan on a large random
+/// signal. With fewer letters than the window no marker exists, so both
+/// have to read the whole signal.
+fn main() {
+    let mut megabytes = 4;
+    let mut window = 14;
+    let mut alphabet = 13;
+    let mut seed = 1;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let number = args.next()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or_else(|| exit_with(format!("{} needs a number\n{}", arg, USAGE)));
+        match arg.as_str() {
+            "--megabytes" => megabytes = number as usize,
+            "--window" => window = (number as usize).max(1),
+            "--alphabet" => alphabet = number.clamp(1, 26) as u8,
+            "--seed" => seed = number,
+            _ => exit_with(format!("unexpected argument {:?}\n{}", arg, USAGE)),
+        }
+    }
+
+    let signal = generate(megabytes << 20, alphabet, seed);
+    println!("{} bytes, window {}, {} letters (seed {})", signal.len(), window, alphabet, seed);
+
+    let start = Instant::now();
+    let fast = find_starter_packet(&signal, window);
+    let shown = match &fast {
+        Ok(idx) => idx.to_string(),
+        Err(error) => error.to_string(),
+    };
+    println!("{:<16}{:>8.3}s  {}", "sliding window", start.elapsed().as_secs_f64(), shown);
+
+    let start = Instant::now();
+    let naive = find_starter_packet_naive(&signal, window);
+    println!("{:<16}{:>8.3}s  {}", "naive", start.elapsed().as_secs_f64(), naive);
+    // The original scan reports a missing marker as one past the end.
+    if fast.unwrap_or(signal.len() + 1) != naive {
+        exit_with("implementations disagree");
+    }
+}
+
+/// Random lowercase letters from an xorshift generator.
+fn generate(len: usize, alphabet: u8, seed: u64) -> String {
+    let mut state = seed.max(1);
+    (0..len)
+        .map(
+            |_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                char::from(b'a' + (state % alphabet as u64) as u8)
+            }
+        )
+        .collect()
+}
+
+fn exit_with(error: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1);
+}
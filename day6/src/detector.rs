@@ -0,0 +1,153 @@
+use std::io::{self, Read};
+
+use crate::error::{missing_marker, MarkerError};
+
+/// Finds a marker in a signal that arrives a piece at a time. Only the
+/// position each byte value was last seen at is kept, so memory does not
+/// grow with the signal. Offsets count bytes, as with [`crate::Unit::Byte`].
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    window: usize,
+    last_seen: [Option<usize>; 256],
+    start: usize,
+    position: usize,
+    marker: Option<usize>,
+}
+
+impl MarkerDetector {
+    pub fn new(window: usize) -> Self {
+        MarkerDetector {
+            window,
+            last_seen: [None; 256],
+            start: 0,
+            position: 0,
+            marker: None,
+        }
+    }
+
+    /// Forgets everything received so far.
+    pub fn reset(&mut self) {
+        *self = MarkerDetector::new(self.window);
+    }
+
+    /// Takes the next byte and returns the marker offset if this byte
+    /// completes it. Once the marker is found further bytes are ignored.
+    /// A window of 0 never completes.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        if self.marker.is_some() || self.window == 0 {
+            return None;
+        }
+        if let Some(previous) = self.last_seen[byte as usize].replace(self.position) {
+            self.start = self.start.max(previous + 1);
+        }
+        self.position += 1;
+        if self.position - self.start >= self.window {
+            self.marker = Some(self.position);
+        }
+        self.marker
+    }
+
+    /// Takes a chunk and returns the marker offset if it completes inside
+    /// it. Bytes after the marker are not read.
+    pub fn feed(&mut self, chunk: &[u8]) -> Option<usize> {
+        chunk.iter().find_map(|byte| self.push(*byte))
+    }
+
+    /// Reads from `reader` until the marker completes or the reader runs dry.
+    pub fn scan(&mut self, mut reader: impl Read) -> io::Result<Option<usize>> {
+        let mut buffer = [0; 8192];
+        while self.marker.is_none() {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.feed(&buffer[..read]);
+        }
+        Ok(self.marker)
+    }
+
+    /// The marker offset, once found.
+    pub fn marker(&self) -> Option<usize> {
+        self.marker
+    }
+
+    /// The marker offset, or why there is none in what was received so far.
+    pub fn result(&self) -> Result<usize, MarkerError> {
+        self.marker.ok_or_else(|| missing_marker(self.position, self.window))
+    }
+
+    /// How many bytes have been taken so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_starter_packet;
+
+    #[test]
+    fn it_matches_find_starter_packet() {
+        let signals = [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ];
+        for signal in signals {
+            for window in [4, 14] {
+                let mut detector = MarkerDetector::new(window);
+                assert_eq!(detector.feed(signal.as_bytes()), find_starter_packet(signal, window).ok());
+            }
+        }
+    }
+
+    #[test]
+    fn it_reports_the_byte_that_completes_the_marker() {
+        let mut detector = MarkerDetector::new(4);
+        let pushed: Vec<_> = b"mjqjpqm".iter().map(|byte| detector.push(*byte)).collect();
+        assert_eq!(pushed, Vec::from([None, None, None, None, None, None, Some(7)]));
+        assert_eq!(detector.push(b'g'), None);
+        assert_eq!(detector.marker(), Some(7));
+        assert_eq!(detector.position(), 7);
+
+        detector.reset();
+        assert_eq!(detector.marker(), None);
+        assert_eq!(detector.feed(b"aab"), None);
+        assert_eq!(detector.feed(b"cd"), Some(5));
+
+        let mut detector = MarkerDetector::new(0);
+        assert_eq!(detector.feed(b"abc"), None);
+        assert_eq!(detector.result(), Err(MarkerError::EmptyWindow));
+    }
+
+    #[test]
+    fn it_scans_a_reader_in_chunks() {
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+                let read = self.0.len().min(buffer.len()).min(3);
+                buffer[..read].copy_from_slice(&self.0[..read]);
+                self.0 = &self.0[read..];
+                Ok(read)
+            }
+        }
+        let mut detector = MarkerDetector::new(14);
+        assert_eq!(detector.scan(Trickle(b"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw")).unwrap(), Some(26));
+        let mut detector = MarkerDetector::new(14);
+        assert_eq!(detector.scan(Trickle(b"abcabcabc")).unwrap(), None);
+        assert_eq!(detector.position(), 9);
+        assert_eq!(detector.result(), Err(MarkerError::TooShort { len: 9, window: 14 }));
+        let mut detector = MarkerDetector::new(4);
+        detector.scan(Trickle(b"abcabcabc")).unwrap();
+        assert_eq!(detector.result(), Err(MarkerError::NotFound { len: 9, window: 4 }));
+    }
+}
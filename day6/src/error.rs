@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt;
+
+/// Why a signal has no marker. Lengths are counted in the unit the signal
+/// was scanned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerError {
+    /// A window of 0 characters cannot hold a marker.
+    EmptyWindow,
+    /// The signal ended before a whole window was received.
+    TooShort { len: usize, window: usize },
+    /// No window of the signal qualifies as a marker.
+    NotFound { len: usize, window: usize },
+    /// The tolerance asks for more distinct characters than the window holds.
+    ImpossibleTolerance { window: usize, distinct: usize },
+}
+
+impl fmt::Display for MarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkerError::EmptyWindow =>
+                write!(f, "window size must be at least 1"),
+            MarkerError::TooShort { len, window } =>
+                write!(f, "signal of {} characters is shorter than the window of {}", len, window),
+            MarkerError::NotFound { len, window } =>
+                write!(f, "no marker of {} characters in a signal of {}", window, len),
+            MarkerError::ImpossibleTolerance { window, distinct } =>
+                write!(f, "a window of {} cannot hold {} distinct characters", window, distinct),
+        }
+    }
+}
+
+impl Error for MarkerError {}
+
+/// Explains why a scan of `len` characters with `window` found nothing.
+pub(crate) fn missing_marker(len: usize, window: usize) -> MarkerError {
+    if window == 0 {
+        MarkerError::EmptyWindow
+    } else if len < window {
+        MarkerError::TooShort { len, window }
+    } else {
+        MarkerError::NotFound { len, window }
+    }
+}
@@ -0,0 +1,303 @@
+pub mod detector;
+pub mod error;
+pub mod markers;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use unicode_segmentation::UnicodeSegmentation;
+use error::{missing_marker, MarkerError};
+
+/// What counts as one character of the signal. Offsets are always reported
+/// in the same unit the signal was scanned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Byte,
+    Char,
+    /// A user-perceived character, such as a letter with its combining accents.
+    Grapheme,
+}
+
+impl Unit {
+    pub fn from_name(name: &str) -> Option<Unit> {
+        match name {
+            "byte" => Some(Unit::Byte),
+            "char" => Some(Unit::Char),
+            "grapheme" => Some(Unit::Grapheme),
+            _ => None,
+        }
+    }
+}
+
+/// Returns how many characters have been read when the last `pack_len`
+/// characters are first all different.
+pub fn find_starter_packet(input: &str, pack_len: usize) -> Result<usize, MarkerError> {
+    find_starter_packet_in(input, pack_len, Unit::Char)
+}
+
+/// Same as [`find_starter_packet`], counting in `unit`.
+pub fn find_starter_packet_in(input: &str, pack_len: usize, unit: Unit) -> Result<usize, MarkerError> {
+    find_starter_packet_with(input, pack_len, unit, Tolerance::Strict)
+}
+
+/// How different the characters of a window must be for it to be a marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tolerance {
+    /// Every character is different.
+    #[default]
+    Strict,
+    /// At most this many characters repeat one seen earlier in the window.
+    MaxRepeats(usize),
+    /// At least this many different characters.
+    MinDistinct(usize),
+}
+
+impl Tolerance {
+    /// How many different characters a window of `window` needs.
+    pub fn min_distinct(&self, window: usize) -> usize {
+        match self {
+            Tolerance::Strict => window,
+            Tolerance::MaxRepeats(repeats) => window.saturating_sub(*repeats),
+            Tolerance::MinDistinct(distinct) => *distinct,
+        }
+    }
+}
+
+/// Same as [`find_starter_packet_in`], accepting windows as noisy as
+/// `tolerance` allows.
+pub fn find_starter_packet_with(input: &str, pack_len: usize, unit: Unit, tolerance: Tolerance) -> Result<usize, MarkerError> {
+    let distinct = tolerance.min_distinct(pack_len);
+    if pack_len > 0 && distinct > pack_len {
+        return Err(MarkerError::ImpossibleTolerance { window: pack_len, distinct });
+    }
+    let (marker, len) = match unit {
+        Unit::Byte => (scan_windows(input.as_bytes(), pack_len, distinct), input.len()),
+        Unit::Char => {
+            let chars: Vec<_> = input.chars().collect();
+            (scan_windows(&chars, pack_len, distinct), chars.len())
+        },
+        Unit::Grapheme => {
+            let graphemes: Vec<_> = input.graphemes(true).collect();
+            (scan_windows(&graphemes, pack_len, distinct), graphemes.len())
+        },
+    };
+    marker.ok_or_else(|| missing_marker(len, pack_len))
+}
+
+/// Uses the last-seen scan when every item must differ and the counting
+/// scan otherwise.
+fn scan_windows<T: Eq + Hash + Copy>(items: &[T], window: usize, distinct: usize) -> Option<usize> {
+    if distinct == window {
+        first_distinct_window(items, window)
+    } else {
+        first_window_with(items, window, distinct)
+    }
+}
+
+/// Finds the end of the first window of `window` items holding at least
+/// `distinct` different ones. How often each item occurs in the window is
+/// updated as the window slides, so this is one pass as well.
+pub fn first_window_with<T: Eq + Hash + Copy>(items: &[T], window: usize, distinct: usize) -> Option<usize> {
+    if window == 0 {
+        return None;
+    }
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        *counts.entry(*item).or_insert(0) += 1;
+        if i >= window {
+            let old = items[i - window];
+            let count = counts.get_mut(&old).expect("item is in the window");
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&old);
+            }
+        }
+        if i + 1 >= window && counts.len() >= distinct {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// Finds the end of the first window of `window` distinct items in one pass.
+/// Remembering where each item was last seen tells how far back the current
+/// run of distinct items reaches, so every item is looked at once no matter
+/// how large the window is. An empty window never matches.
+pub fn first_distinct_window<T: Eq + Hash + Copy>(items: &[T], window: usize) -> Option<usize> {
+    if window == 0 {
+        return None;
+    }
+    let mut last_seen = HashMap::new();
+    let mut start = 0;
+    for (i, item) in items.iter().enumerate() {
+        if let Some(previous) = last_seen.insert(*item, i) {
+            start = start.max(previous + 1);
+        }
+        if i + 1 - start >= window {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// The original scan, which builds a set for every window and returns
+/// `input.len()+1` when there is no marker. Kept to check and benchmark
+/// [`find_starter_packet`] against.
+pub fn find_starter_packet_naive(input: &str, pack_len: usize) -> usize {
+    if input.len() < pack_len {
+        return input.len()+1;
+    }
+
+    let chars: Vec<_> = input.chars().collect();
+    for i in 0..=(chars.len() - pack_len) {
+        let mut local_set = HashSet::new();
+        for v in 0..pack_len {
+            let this_char = chars[i+v];
+            if local_set.contains(&this_char) {
+                break;
+            }
+            local_set.insert(this_char);
+        }
+        if local_set.len() >= pack_len {
+            return i+pack_len;
+        }
+    }
+    input.len()+1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_find_starter_packet() {
+        let actual = find_starter_packet("", 4);
+        let expect = Err(MarkerError::TooShort { len: 0, window: 4 });
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("1234", 4);
+        let expect = Ok(4);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("12", 4);
+        let expect = Err(MarkerError::TooShort { len: 2, window: 4 });
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("4234", 4);
+        let expect = Err(MarkerError::NotFound { len: 4, window: 4 });
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4);
+        let expect = Ok(7);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("bvwbjplbgvbhsrlpgdmjqwftvncz", 4);
+        let expect = Ok(5);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("nppdvjthqldpwncqszvftbrmjlhg", 4);
+        let expect = Ok(6);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4);
+        let expect = Ok(10);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4);
+        let expect = Ok(11);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14);
+        let expect = Ok(19);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("bvwbjplbgvbhsrlpgdmjqwftvncz", 14);
+        let expect = Ok(23);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("nppdvjthqldpwncqszvftbrmjlhg", 14);
+        let expect = Ok(23);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14);
+        let expect = Ok(29);
+        assert_eq!(actual, expect);
+
+        let actual = find_starter_packet("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14);
+        let expect = Ok(26);
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn it_matches_the_naive_scan() {
+        let signals = [
+            "",
+            "a",
+            "aaaa",
+            "abcabcabcd",
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+            "abcdefghijklmnaopqrstuvwxyz",
+        ];
+        for signal in signals {
+            for pack_len in 0..16 {
+                let expect = match find_starter_packet_naive(signal, pack_len) {
+                    _ if pack_len == 0 => Err(MarkerError::EmptyWindow),
+                    i if i > signal.len() => Err(missing_marker(signal.len(), pack_len)),
+                    i => Ok(i),
+                };
+                assert_eq!(find_starter_packet(signal, pack_len), expect, "{:?} {}", signal, pack_len);
+            }
+        }
+    }
+
+    #[test]
+    fn it_counts_in_the_chosen_unit() {
+        let signal = "ééaβγ";
+        assert_eq!(find_starter_packet_in(signal, 3, Unit::Char), Ok(4));
+        assert_eq!(find_starter_packet_in(signal, 3, Unit::Byte), Ok(5));
+        assert_eq!(find_starter_packet_in(signal, 3, Unit::Grapheme), Ok(4));
+
+        let combining = "e\u{301}e\u{301}ab";
+        assert_eq!(find_starter_packet_in(combining, 3, Unit::Grapheme), Ok(4));
+        assert_eq!(find_starter_packet_in(combining, 3, Unit::Char), Ok(5));
+        assert_eq!(find_starter_packet_in(combining, 3, Unit::Byte), Ok(3));
+
+        assert_eq!(find_starter_packet("ééé", 2), Err(MarkerError::NotFound { len: 3, window: 2 }));
+        assert_eq!(find_starter_packet_in("ééé", 2, Unit::Byte), Ok(2));
+        assert_eq!(find_starter_packet_in("ééé", 4, Unit::Grapheme), Err(MarkerError::TooShort { len: 3, window: 4 }));
+        assert_eq!(find_starter_packet("日本", 3), Err(MarkerError::TooShort { len: 2, window: 3 }));
+        assert_eq!(find_starter_packet("日本", 0), Err(MarkerError::EmptyWindow));
+    }
+
+    #[test]
+    fn it_tolerates_noise() {
+        let signal = "aabbcadefg";
+        assert_eq!(find_starter_packet(signal, 4), Ok(7));
+        let with = |tolerance| find_starter_packet_with(signal, 4, Unit::Char, tolerance);
+        assert_eq!(with(Tolerance::Strict), Ok(7));
+        assert_eq!(with(Tolerance::MaxRepeats(1)), Ok(5));
+        assert_eq!(with(Tolerance::MinDistinct(3)), Ok(5));
+        assert_eq!(with(Tolerance::MinDistinct(2)), Ok(4));
+        assert_eq!(with(Tolerance::MaxRepeats(4)), Ok(4));
+        assert_eq!(with(Tolerance::MinDistinct(5)), Err(MarkerError::ImpossibleTolerance { window: 4, distinct: 5 }));
+        assert_eq!(
+            find_starter_packet_with("aaaaaa", 4, Unit::Char, Tolerance::MaxRepeats(2)),
+            Err(MarkerError::NotFound { len: 6, window: 4 }),
+        );
+        assert_eq!(
+            find_starter_packet_with("éée", 2, Unit::Byte, Tolerance::MaxRepeats(1)),
+            Ok(2),
+        );
+    }
+
+    #[test]
+    fn it_matches_strict_mode_when_counting() {
+        let signals = ["mjqjpqmgbljsphdztnvjfqwrcgsmlb", "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", "aaaa", ""];
+        for signal in signals {
+            let chars: Vec<_> = signal.chars().collect();
+            for window in 1..16 {
+                assert_eq!(first_window_with(&chars, window, window), first_distinct_window(&chars, window));
+            }
+        }
+    }
+}
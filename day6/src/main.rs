@@ -1,100 +1,115 @@
-use std::fs;
-use std::collections::HashSet;
-
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+use day6::{find_starter_packet_with, Tolerance, Unit};
+use day6::error::MarkerError;
+use day6::detector::MarkerDetector;
+use day6::markers::{find_markers, Overlap};
+
+const USAGE: &str = concat!(
+    "usage: day6 [FILE|-] [--all] [--unit byte|char|grapheme] [--window N]...\n",
+    "            [--max-repeats D | --min-distinct K]",
+);
+
+/// Reads the signal from the file given as an argument, `-` for stdin, or
+/// `input.txt`, and finds both markers, counting characters unless `--unit`
+/// says otherwise. Bytes are found in a single streaming pass. `--all` lists
+/// every marker instead. `--window` replaces the usual windows of 4 and 14,
+/// and `--max-repeats` or `--min-distinct` accept noisy markers.
 fn main() {
-    let content = fs::read_to_string("input.txt")
-        .expect("should have input.txt");
-    let input = content.as_str();
-
-    let starter_idx = find_starter_packet(input, 4);
-    println!("Idx: {}", starter_idx);
-
-    let starter_m_idx = find_starter_packet(input, 14);
-    println!("Idx: {}", starter_m_idx);
-}
-
-fn find_starter_packet(input: &str, pack_len: usize) -> usize {
-    if input.len() < pack_len {
-        return input.len()+1;
+    let mut path = String::from("input.txt");
+    let mut all = false;
+    let mut unit = Unit::Char;
+    let mut windows = Vec::new();
+    let mut tolerance = Tolerance::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || args.next()
+            .and_then(|value| value.parse::<usize>().ok())
+            .unwrap_or_else(|| exit_with(format!("{} needs a number\n{}", arg, USAGE)));
+        match arg.as_str() {
+            "--all" => all = true,
+            "--window" => windows.push(number()),
+            "--max-repeats" => tolerance = Tolerance::MaxRepeats(number()),
+            "--min-distinct" => tolerance = Tolerance::MinDistinct(number()),
+            "--unit" => unit = args.next()
+                .and_then(|name| Unit::from_name(&name))
+                .unwrap_or_else(|| exit_with(format!("--unit needs byte, char or grapheme\n{}", USAGE))),
+            _ if !arg.starts_with("--") || arg == "-" => path = arg,
+            _ => exit_with(format!("unexpected argument {:?}\n{}", arg, USAGE)),
+        }
     }
-
-    let chars: Vec<_> = input.chars().collect();
-    for i in 0..=(chars.len() - pack_len) {
-        let mut local_set = HashSet::new();
-        for v in 0..pack_len {
-            let this_char = chars[i+v];
-            if local_set.contains(&this_char) {
+    if windows.is_empty() {
+        windows = Vec::from([4, 14]);
+    }
+    let mut reader: Box<dyn Read> = if path == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(&path).unwrap_or_else(|e| exit_with(format!("{}: {}", path, e))))
+    };
+    let source = if path == "-" { "stdin" } else { path.as_str() };
+
+    if unit == Unit::Byte && tolerance == Tolerance::Strict && !all {
+        let mut detectors: Vec<_> = windows.iter().map(|window| MarkerDetector::new(*window)).collect();
+        let mut buffer = [0; 8192];
+        while detectors.iter().any(|detector| detector.marker().is_none()) {
+            let read = reader.read(&mut buffer).unwrap_or_else(|e| exit_with(format!("{}: {}", source, e)));
+            if read == 0 {
                 break;
             }
-            local_set.insert(this_char);
+            for detector in &mut detectors {
+                detector.feed(&buffer[..read]);
+            }
         }
-        if local_set.len() >= pack_len {
-            return i+pack_len;
+        let found: Vec<_> = detectors.iter().map(|detector| report(detector.result())).collect();
+        if found.contains(&false) {
+            process::exit(1);
         }
+        return;
     }
-    return input.len()+1;
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_find_starter_packet() {
-        let actual = find_starter_packet("", 4);
-        let expect = 1;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("1234", 4);
-        let expect = 4;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("12", 4);
-        let expect = 3;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("4234", 4);
-        let expect = 5;
-        assert_eq!(actual, expect);
 
-        let actual = find_starter_packet("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4);
-        let expect = 7;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("bvwbjplbgvbhsrlpgdmjqwftvncz", 4);
-        let expect = 5;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("nppdvjthqldpwncqszvftbrmjlhg", 4);
-        let expect = 6;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4);
-        let expect = 10;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4);
-        let expect = 11;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14);
-        let expect = 19;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("bvwbjplbgvbhsrlpgdmjqwftvncz", 14);
-        let expect = 23;
-        assert_eq!(actual, expect);
-
-        let actual = find_starter_packet("nppdvjthqldpwncqszvftbrmjlhg", 14);
-        let expect = 23;
-        assert_eq!(actual, expect);
+    let mut signal = String::new();
+    reader.read_to_string(&mut signal).unwrap_or_else(|e| exit_with(format!("{}: {}", source, e)));
+    if all {
+        if tolerance != Tolerance::Strict {
+            exit_with("--all finds strict markers only");
+        }
+        for window in windows {
+            let overlapping = find_markers(&signal, window, unit, Overlap::Overlapping).count();
+            let markers: Vec<_> = find_markers(&signal, window, unit, Overlap::NonOverlapping).collect();
+            println!("Window {}: {} markers, {} without overlap", window, overlapping, markers.len());
+            for marker in markers {
+                println!("  {:>8}  {}", marker.end, String::from_utf8_lossy(marker.text));
+            }
+        }
+        return;
+    }
 
-        let actual = find_starter_packet("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14);
-        let expect = 29;
-        assert_eq!(actual, expect);
+    let found: Vec<_> = windows.iter()
+        .map(|window| report(find_starter_packet_with(&signal, *window, unit, tolerance)))
+        .collect();
+    if found.contains(&false) {
+        process::exit(1);
+    }
+}
 
-        let actual = find_starter_packet("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14);
-        let expect = 26;
-        assert_eq!(actual, expect);
+/// Prints a marker offset, or why there is none, and tells whether one was
+/// found.
+fn report(result: Result<usize, MarkerError>) -> bool {
+    match result {
+        Ok(idx) => {
+            println!("Idx: {}", idx);
+            true
+        },
+        Err(error) => {
+            println!("Idx: none, {}", error);
+            false
+        },
     }
 }
+
+fn exit_with(error: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1);
+}
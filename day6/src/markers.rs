@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::str;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::Unit;
+
+/// Whether markers may share characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Every position where the last `window` units are distinct.
+    Overlapping,
+    /// A marker only counts once it starts after the previous one ended, as
+    /// if the device locked on and then started listening afresh.
+    NonOverlapping,
+}
+
+/// A run of distinct units. `start` and `end` count units from the
+/// beginning of the signal, so `end` is what [`crate::find_starter_packet_in`]
+/// reports for the first marker. A run of bytes may cut a character in two,
+/// which is why `text` holds the bytes of the marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub text: &'a [u8],
+}
+
+impl<'a> Marker<'a> {
+    /// The marker as text, unless it starts or ends inside a character.
+    pub fn as_str(&self) -> Option<&'a str> {
+        str::from_utf8(self.text).ok()
+    }
+}
+
+/// Iterator over the markers of a signal, see [`find_markers`].
+pub struct Markers<'a> {
+    input: &'a [u8],
+    /// Byte offset and bytes of every unit of the signal.
+    units: Box<dyn Iterator<Item = (usize, &'a [u8])> + 'a>,
+    window: usize,
+    overlap: Overlap,
+    last_seen: HashMap<&'a [u8], usize>,
+    /// Byte offset of every unit of the current window, oldest first.
+    offsets: Vec<usize>,
+    start: usize,
+    position: usize,
+}
+
+impl<'a> Iterator for Markers<'a> {
+    type Item = Marker<'a>;
+
+    fn next(&mut self) -> Option<Marker<'a>> {
+        if self.window == 0 {
+            return None;
+        }
+        for (offset, unit) in self.units.by_ref() {
+            if let Some(previous) = self.last_seen.insert(unit, self.position) {
+                self.start = self.start.max(previous + 1);
+            }
+            self.offsets[self.position % self.window] = offset;
+            self.position += 1;
+            if self.position - self.start < self.window {
+                continue;
+            }
+            let first = self.offsets[self.position % self.window];
+            let marker = Marker {
+                start: self.position - self.window,
+                end: self.position,
+                text: &self.input[first..(offset + unit.len())],
+            };
+            if self.overlap == Overlap::NonOverlapping {
+                self.start = self.position;
+            }
+            return Some(marker);
+        }
+        None
+    }
+}
+
+/// Every marker of `window` distinct units in `input`, in order. A window
+/// of 0 has no markers.
+pub fn find_markers(input: &str, window: usize, unit: Unit, overlap: Overlap) -> Markers<'_> {
+    let bytes = input.as_bytes();
+    let units: Box<dyn Iterator<Item = (usize, &[u8])>> = match unit {
+        Unit::Byte => Box::new((0..bytes.len()).map(move |i| (i, &bytes[i..=i]))),
+        Unit::Char => Box::new(input.char_indices().map(move |(i, c)| (i, &bytes[i..(i + c.len_utf8())]))),
+        Unit::Grapheme => Box::new(input.grapheme_indices(true).map(|(i, g)| (i, g.as_bytes()))),
+    };
+    Markers {
+        input: bytes,
+        units,
+        window,
+        overlap,
+        last_seen: HashMap::new(),
+        offsets: vec![0; window],
+        start: 0,
+        position: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{find_starter_packet, find_starter_packet_in};
+
+    #[test]
+    fn it_finds_overlapping_markers() {
+        let markers: Vec<_> = find_markers("mjqjpqmgb", 4, Unit::Char, Overlap::Overlapping).collect();
+        let expect = Vec::from([
+            Marker { start: 3, end: 7, text: b"jpqm" },
+            Marker { start: 4, end: 8, text: b"pqmg" },
+            Marker { start: 5, end: 9, text: b"qmgb" },
+        ]);
+        assert_eq!(markers, expect);
+
+        let signal = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
+        let first = find_markers(signal, 14, Unit::Char, Overlap::Overlapping).next().unwrap();
+        assert_eq!(Ok(first.end), find_starter_packet(signal, 14));
+    }
+
+    #[test]
+    fn it_finds_non_overlapping_markers() {
+        let markers: Vec<_> = find_markers("abcdefghij", 3, Unit::Char, Overlap::NonOverlapping)
+            .map(|marker| marker.as_str().unwrap())
+            .collect();
+        assert_eq!(markers, Vec::from(["abc", "def", "ghi"]));
+
+        let markers: Vec<_> = find_markers("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4, Unit::Char, Overlap::NonOverlapping)
+            .map(|marker| (marker.end, marker.as_str().unwrap()))
+            .collect();
+        assert_eq!(markers, Vec::from([(7, "jpqm"), (11, "gblj"), (15, "sphd"), (19, "ztnv"), (23, "jfqw"), (27, "rcgs")]));
+    }
+
+    #[test]
+    fn it_counts_markers() {
+        assert_eq!(find_markers("aaaa", 2, Unit::Char, Overlap::Overlapping).count(), 0);
+        assert_eq!(find_markers("abab", 2, Unit::Char, Overlap::Overlapping).count(), 3);
+        assert_eq!(find_markers("abab", 2, Unit::Char, Overlap::NonOverlapping).count(), 2);
+        assert_eq!(find_markers("abab", 0, Unit::Char, Overlap::Overlapping).count(), 0);
+    }
+
+    #[test]
+    fn it_slices_multibyte_markers() {
+        let markers: Vec<_> = find_markers("ééaβγ", 3, Unit::Char, Overlap::Overlapping).collect();
+        let expect = Vec::from([
+            Marker { start: 1, end: 4, text: "éaβ".as_bytes() },
+            Marker { start: 2, end: 5, text: "aβγ".as_bytes() },
+        ]);
+        assert_eq!(markers, expect);
+    }
+
+    #[test]
+    fn it_counts_in_the_chosen_unit() {
+        let signal = "ééaβγ";
+        let markers: Vec<_> = find_markers(signal, 3, Unit::Byte, Overlap::Overlapping).collect();
+        let ends: Vec<_> = markers.iter().map(|marker| marker.end).collect();
+        assert_eq!(ends, Vec::from([5, 6, 7, 9]));
+        assert_eq!(Ok(markers[0].end), find_starter_packet_in(signal, 3, Unit::Byte));
+        assert_eq!(markers[0].as_str(), Some("éa"));
+        assert_eq!(markers[1].as_str(), None);
+
+        let signal = "e\u{301}e\u{301}ab";
+        let marker = find_markers(signal, 3, Unit::Grapheme, Overlap::Overlapping).next().unwrap();
+        assert_eq!((marker.start, marker.end, marker.as_str()), (1, 4, Some("e\u{301}ab")));
+        assert_eq!(Ok(marker.end), find_starter_packet_in(signal, 3, Unit::Grapheme));
+        let marker = find_markers(signal, 3, Unit::Char, Overlap::Overlapping).next().unwrap();
+        assert_eq!((marker.start, marker.end, marker.as_str()), (2, 5, Some("e\u{301}a")));
+    }
+}